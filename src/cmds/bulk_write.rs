@@ -0,0 +1,777 @@
+use super::concern::{with_write_flags, write_concern};
+use super::val_converter::{BsonOptions, bson_to_value, value_to_doc};
+use crate::MongoPlugin;
+use crate::error::mongo_error;
+use mongodb::Namespace;
+use mongodb::bson::{Bson, Document, doc, from_document, oid::ObjectId, to_bson, to_vec};
+use mongodb::error::{ErrorKind, PartialBulkWriteResult, WriteConcernError, WriteError};
+use mongodb::options::{
+    DeleteManyModel, DeleteOneModel, InsertOneModel, ReplaceOneModel, UpdateManyModel,
+    UpdateModifications, UpdateOneModel, WriteModel,
+};
+use mongodb::results::VerboseBulkWriteResult;
+use mongodb::sync::{Client, Database};
+use nu_plugin::{DynamicCompletionCall, EngineInterface, EvaluatedCall, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Signature, Span, SyntaxShape, Type, Value,
+    engine::ArgType, record,
+};
+use std::collections::{BTreeMap, HashMap};
+
+pub struct BulkWrite;
+
+impl SimplePluginCommand for BulkWrite {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc bulk-write"
+    }
+
+    fn description(&self) -> &str {
+        "run a table of insert, update, replace and delete operations as a bulk write"
+    }
+
+    fn extra_description(&self) -> &str {
+        r#"Each input record is an operation:
+  {op: insert, doc: <document>}
+  {op: update, filter: <query>, update: <update or pipeline>, upsert: <bool>, multi: <bool>}
+  {op: replace, filter: <query>, doc: <document>, upsert: <bool>}
+  {op: delete, filter: <query>, multi: <bool>}
+An operation can set `coll` to write into another collection.
+
+Operations are sent in one request on mongodb 8.0+.  On older servers, consecutive operations of
+the same kind on the same collection are sent in one insert, update or delete command."#
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        with_write_flags(Signature::build("mongoc bulk-write"))
+            .named(
+                "collection",
+                SyntaxShape::String,
+                "collection name of operations without `coll`",
+                Some('c'),
+            )
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .switch(
+                "unordered",
+                "keep running remaining operations after an operation fails",
+                None,
+            )
+            .input_output_types(vec![
+                (Type::table(), Type::record()),
+                (Type::record(), Type::record()),
+            ])
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "insert a student, and remove another one in `students` collection",
+                example: "[{op: insert, doc: {name: Alice, age: 19}}, {op: delete, filter: {name: Bob}}] | mongoc bulk-write -c students",
+                result: None,
+            },
+            Example {
+                description: "update students, and log it into `logs` collection, don't stop on errors",
+                example: "[{op: update, filter: {age: 19}, update: {$inc: {age: 1}}, multi: true}, {op: insert, coll: logs, doc: {msg: 'age increased'}}] | mongoc bulk-write -c students --unordered",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, client, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (
+                    handle_id,
+                    plugin.get_client(handle_id, call.head)?,
+                    plugin.get_handle(handle_id, call.head)?,
+                )
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (
+                    handle_id,
+                    plugin.get_client(handle_id, db_handle.span())?,
+                    plugin.get_handle(handle_id, db_handle.span())?,
+                )
+            }
+        };
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
+        let coll: Option<String> = call.get_flag("collection")?;
+        let ops = match input {
+            Value::List { vals, .. } => vals
+                .iter()
+                .map(|v| WriteOp::parse(v, coll.as_deref(), &bson_options))
+                .collect::<Result<Vec<_>, _>>()?,
+            other => vec![WriteOp::parse(other, coll.as_deref(), &bson_options)?],
+        };
+        let ordered = !call.has_flag("unordered")?;
+
+        let outcome = match client_bulk_write(&client, &db, &ops, ordered, call)? {
+            Some(outcome) => outcome,
+            None => run_batches(&db, &ops, ordered, call)?,
+        };
+        // collections may be created by the operations.
+        plugin.invalidate_completions(handle_id, None);
+        Ok(to_output(&ops, outcome, call.head, &bson_options))
+    }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "collection" => {
+                super::get_collection_names_at_handle(plugin, engine, &call)
+            }
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
+
+enum OpKind {
+    Insert(Document),
+    Update {
+        filter: Document,
+        update: UpdateModifications,
+        upsert: Option<bool>,
+        multi: bool,
+    },
+    Replace {
+        filter: Document,
+        replacement: Document,
+        upsert: Option<bool>,
+    },
+    Delete {
+        filter: Document,
+        multi: bool,
+    },
+}
+
+struct WriteOp {
+    coll: String,
+    kind: OpKind,
+}
+
+/// Result of an operation, fields which don't apply to the operation are None.
+#[derive(Default)]
+struct OpResult {
+    inserted_id: Option<Bson>,
+    matched: Option<u64>,
+    modified: Option<u64>,
+    upserted_id: Option<Bson>,
+    deleted: Option<u64>,
+    error: Option<String>,
+}
+
+impl WriteOp {
+    fn parse(
+        val: &Value,
+        default_coll: Option<&str>,
+        bson_options: &BsonOptions,
+    ) -> Result<Self, LabeledError> {
+        let span = val.span();
+        let record = val.as_record()?;
+        let invalid = |msg: &str| LabeledError::new("invalid operation").with_label(msg, span);
+        let get_doc = |name: &str| -> Result<Document, LabeledError> {
+            let bson_options = if name == "filter" {
+                bson_options.filter()
+            } else {
+                bson_options.clone()
+            };
+            match record.get(name) {
+                Some(Value::Record { val, .. }) => {
+                    value_to_doc(val.clone().into_owned(), &bson_options)
+                }
+                Some(other) => Err(LabeledError::new("invalid operation")
+                    .with_label(format!("`{name}` should be a record"), other.span())),
+                None => Err(invalid(&format!("`{name}` is required"))),
+            }
+        };
+        let get_bool = |name: &str| -> Result<Option<bool>, LabeledError> {
+            match record.get(name) {
+                None => Ok(None),
+                Some(v) => Ok(Some(v.as_bool()?)),
+            }
+        };
+
+        let coll = match record.get("coll") {
+            Some(coll) => coll.as_str()?.to_string(),
+            None => default_coll
+                .ok_or_else(|| invalid("`coll` is required when `--collection` is not given"))?
+                .to_string(),
+        };
+        let op = record
+            .get("op")
+            .ok_or_else(|| invalid("`op` is required"))?
+            .as_str()?;
+        let kind = match op {
+            "insert" => OpKind::Insert(get_doc("doc")?),
+            "update" => OpKind::Update {
+                filter: get_doc("filter")?,
+                update: match record.get("update") {
+                    Some(Value::List { vals, .. }) => UpdateModifications::Pipeline(
+                        vals.iter()
+                            .map(|v| value_to_doc(v.as_record()?.clone(), bson_options))
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    _ => UpdateModifications::Document(get_doc("update")?),
+                },
+                upsert: get_bool("upsert")?,
+                multi: get_bool("multi")?.unwrap_or(false),
+            },
+            "replace" => OpKind::Replace {
+                filter: get_doc("filter")?,
+                replacement: get_doc("doc")?,
+                upsert: get_bool("upsert")?,
+            },
+            "delete" => OpKind::Delete {
+                filter: get_doc("filter")?,
+                multi: get_bool("multi")?.unwrap_or(false),
+            },
+            _ => {
+                return Err(invalid(
+                    "`op` should be one of insert, update, replace, delete",
+                ));
+            }
+        };
+        Ok(Self { coll, kind })
+    }
+
+    fn name(&self) -> &'static str {
+        match self.kind {
+            OpKind::Insert(_) => "insert",
+            OpKind::Update { .. } => "update",
+            OpKind::Replace { .. } => "replace",
+            OpKind::Delete { .. } => "delete",
+        }
+    }
+
+    fn to_model(&self, db_name: &str) -> WriteModel {
+        let namespace = Namespace::new(db_name, &self.coll);
+        match &self.kind {
+            OpKind::Insert(doc) => InsertOneModel::builder()
+                .namespace(namespace)
+                .document(doc.clone())
+                .build()
+                .into(),
+            OpKind::Update {
+                filter,
+                update,
+                upsert,
+                multi: false,
+            } => UpdateOneModel::builder()
+                .namespace(namespace)
+                .filter(filter.clone())
+                .update(update.clone())
+                .upsert(*upsert)
+                .build()
+                .into(),
+            OpKind::Update {
+                filter,
+                update,
+                upsert,
+                multi: true,
+            } => UpdateManyModel::builder()
+                .namespace(namespace)
+                .filter(filter.clone())
+                .update(update.clone())
+                .upsert(*upsert)
+                .build()
+                .into(),
+            OpKind::Replace {
+                filter,
+                replacement,
+                upsert,
+            } => ReplaceOneModel::builder()
+                .namespace(namespace)
+                .filter(filter.clone())
+                .replacement(replacement.clone())
+                .upsert(*upsert)
+                .build()
+                .into(),
+            OpKind::Delete {
+                filter,
+                multi: false,
+            } => DeleteOneModel::builder()
+                .namespace(namespace)
+                .filter(filter.clone())
+                .build()
+                .into(),
+            OpKind::Delete {
+                filter,
+                multi: true,
+            } => DeleteManyModel::builder()
+                .namespace(namespace)
+                .filter(filter.clone())
+                .build()
+                .into(),
+        }
+    }
+
+    fn command_kind(&self) -> CommandKind {
+        match self.kind {
+            OpKind::Insert(_) => CommandKind::Insert,
+            OpKind::Update { .. } | OpKind::Replace { .. } => CommandKind::Update,
+            OpKind::Delete { .. } => CommandKind::Delete,
+        }
+    }
+
+    /// Statement of the operation in `insert`, `update` or `delete` command, `_id` of inserted
+    /// documents is generated here, so it can be reported.
+    fn to_statement(&self) -> Document {
+        match &self.kind {
+            OpKind::Insert(doc) if doc.contains_key("_id") => doc.clone(),
+            OpKind::Insert(doc) => {
+                let mut with_id = doc! { "_id": ObjectId::new() };
+                with_id.extend(doc.clone());
+                with_id
+            }
+            OpKind::Update {
+                filter,
+                update,
+                upsert,
+                multi,
+            } => {
+                let u = match update {
+                    UpdateModifications::Document(d) => Bson::Document(d.clone()),
+                    UpdateModifications::Pipeline(stages) => {
+                        Bson::Array(stages.iter().cloned().map(Bson::Document).collect())
+                    }
+                    _ => unreachable!("only documents and pipelines are parsed"),
+                };
+                doc! {
+                    "q": filter.clone(),
+                    "u": u,
+                    "upsert": upsert.unwrap_or(false),
+                    "multi": *multi,
+                }
+            }
+            OpKind::Replace {
+                filter,
+                replacement,
+                upsert,
+            } => doc! {
+                "q": filter.clone(),
+                "u": replacement.clone(),
+                "upsert": upsert.unwrap_or(false),
+                "multi": false,
+            },
+            OpKind::Delete { filter, multi } => doc! {
+                "q": filter.clone(),
+                "limit": if *multi { 0 } else { 1 },
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CommandKind {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// Results of all operations, operations which are not executed have no result.
+#[derive(Default)]
+struct Outcome {
+    results: Vec<Option<OpResult>>,
+    counts: Counts,
+    write_concern_errors: Vec<String>,
+}
+
+#[derive(Default)]
+struct Counts {
+    inserted: u64,
+    matched: u64,
+    modified: u64,
+    upserted: u64,
+    deleted: u64,
+}
+
+impl Counts {
+    fn of(results: &[Option<OpResult>]) -> Self {
+        let mut counts = Self::default();
+        for result in results.iter().flatten() {
+            counts.inserted += u64::from(result.inserted_id.is_some());
+            counts.matched += result.matched.unwrap_or_default();
+            counts.modified += result.modified.unwrap_or_default();
+            counts.upserted += u64::from(result.upserted_id.is_some());
+            counts.deleted += result.deleted.unwrap_or_default();
+        }
+        counts
+    }
+}
+
+/// Run operations by client bulk write, returns None if the server doesn't support it, which
+/// requires mongodb 8.0+.
+fn client_bulk_write(
+    client: &Client,
+    db: &Database,
+    ops: &[WriteOp],
+    ordered: bool,
+    call: &EvaluatedCall,
+) -> Result<Option<Outcome>, LabeledError> {
+    let models: Vec<WriteModel> = ops.iter().map(|op| op.to_model(db.name())).collect();
+    let mut action = client.bulk_write(models).verbose_results().ordered(ordered);
+    if let Some(wc) = write_concern(call)? {
+        action = action.write_concern(wc);
+    }
+    let err = match action.run() {
+        Ok(result) => {
+            let results = verbose_results(ops.len(), result);
+            return Ok(Some(Outcome {
+                counts: Counts::of(&results),
+                results,
+                write_concern_errors: vec![],
+            }));
+        }
+        Err(e) => e,
+    };
+    match err.kind.as_ref() {
+        ErrorKind::IncompatibleServer { .. } => Ok(None),
+        ErrorKind::BulkWrite(bulk_err) => {
+            let mut results = match &bulk_err.partial_result {
+                Some(PartialBulkWriteResult::Verbose(result)) => {
+                    verbose_results(ops.len(), result.clone())
+                }
+                _ => (0..ops.len()).map(|_| None).collect(),
+            };
+            for (index, write_error) in &bulk_err.write_errors {
+                if let Some(result) = results.get_mut(*index) {
+                    *result = Some(OpResult {
+                        error: Some(write_error.message.clone()),
+                        ..Default::default()
+                    });
+                }
+            }
+            Ok(Some(Outcome {
+                counts: Counts::of(&results),
+                results,
+                write_concern_errors: bulk_err
+                    .write_concern_errors
+                    .iter()
+                    .map(|e| e.message.clone())
+                    .collect(),
+            }))
+        }
+        _ => Err(mongo_error(err, call)),
+    }
+}
+
+fn verbose_results(len: usize, mut result: VerboseBulkWriteResult) -> Vec<Option<OpResult>> {
+    (0..len)
+        .map(|index| {
+            if let Some(r) = result.insert_results.remove(&index) {
+                Some(OpResult {
+                    inserted_id: Some(r.inserted_id),
+                    ..Default::default()
+                })
+            } else if let Some(r) = result.update_results.remove(&index) {
+                Some(OpResult {
+                    matched: Some(r.matched_count),
+                    modified: Some(r.modified_count),
+                    upserted_id: r.upserted_id,
+                    ..Default::default()
+                })
+            } else {
+                result.delete_results.remove(&index).map(|r| OpResult {
+                    deleted: Some(r.deleted_count),
+                    ..Default::default()
+                })
+            }
+        })
+        .collect()
+}
+
+/// Max number of operations in one write command.
+pub(super) const MAX_BATCH_OPS: usize = 1000;
+/// Max size of statements in one write command, which is limited to 16MB by server.
+pub(super) const MAX_BATCH_BYTES: usize = 12 * 1024 * 1024;
+
+/// Run operations by `insert`, `update` and `delete` commands, for servers before mongodb 8.0.
+///
+/// Consecutive operations of the same kind on the same collection are sent in one command, so the
+/// server runs them in order or not.  The server only reports total counts of a command, so
+/// matched, modified and deleted counts of an operation are reported when it's sent alone.
+fn run_batches(
+    db: &Database,
+    ops: &[WriteOp],
+    ordered: bool,
+    call: &EvaluatedCall,
+) -> Result<Outcome, LabeledError> {
+    let write_concern = write_concern(call)?
+        .map(|wc| to_bson(&wc))
+        .transpose()
+        .map_err(|e| LabeledError::new(format!("{e}")))?;
+    let statements: Vec<Document> = ops.iter().map(WriteOp::to_statement).collect();
+    let mut outcome = Outcome {
+        results: (0..ops.len()).map(|_| None).collect(),
+        ..Default::default()
+    };
+    let mut start = 0;
+    while start < ops.len() {
+        let mut end = start + 1;
+        let mut bytes = statement_size(&statements[start]);
+        while end < ops.len()
+            && end - start < MAX_BATCH_OPS
+            && ops[end].coll == ops[start].coll
+            && ops[end].command_kind() == ops[start].command_kind()
+        {
+            bytes += statement_size(&statements[end]);
+            if bytes > MAX_BATCH_BYTES {
+                break;
+            }
+            end += 1;
+        }
+        let batch = Batch {
+            coll: &ops[start].coll,
+            kind: ops[start].command_kind(),
+            statements: &statements[start..end],
+            offset: start,
+        };
+        let failed = batch.run(db, ordered, write_concern.as_ref(), &mut outcome, call);
+        if failed && ordered {
+            break;
+        }
+        start = end;
+    }
+    Ok(outcome)
+}
+
+pub(super) fn statement_size(statement: &Document) -> usize {
+    to_vec(statement).map(|v| v.len()).unwrap_or_default()
+}
+
+struct Batch<'a> {
+    coll: &'a str,
+    kind: CommandKind,
+    statements: &'a [Document],
+    /// index of the first operation in all operations.
+    offset: usize,
+}
+
+impl Batch<'_> {
+    /// Send the command and record results into `outcome`, returns if any operation failed.
+    fn run(
+        &self,
+        db: &Database,
+        ordered: bool,
+        write_concern: Option<&Bson>,
+        outcome: &mut Outcome,
+        call: &EvaluatedCall,
+    ) -> bool {
+        let (name, field) = match self.kind {
+            CommandKind::Insert => ("insert", "documents"),
+            CommandKind::Update => ("update", "updates"),
+            CommandKind::Delete => ("delete", "deletes"),
+        };
+        let mut command = Document::new();
+        command.insert(name, self.coll);
+        command.insert(field, self.statements.to_vec());
+        command.insert("ordered", ordered);
+        if let Some(wc) = write_concern {
+            command.insert("writeConcern", wc.clone());
+        }
+        let reply = match db.run_command(command).run() {
+            Ok(reply) => reply,
+            Err(e) => {
+                // none of the operations is known to be written.
+                let message = mongo_error(e, call).msg;
+                for index in 0..self.statements.len() {
+                    outcome.results[self.offset + index] = Some(OpResult {
+                        error: Some(message.clone()),
+                        ..Default::default()
+                    });
+                }
+                return true;
+            }
+        };
+
+        let n = count_of(&reply, "n");
+        let mut write_errors: BTreeMap<usize, String> = write_errors(&reply).collect();
+        let mut upserted: HashMap<usize, Bson> = indexed_docs(&reply, "upserted")
+            .filter_map(|(index, d)| Some((index, d.get("_id")?.clone())))
+            .collect();
+        if let Some(wc_err) = write_concern_error(&reply) {
+            outcome.write_concern_errors.push(wc_err);
+        }
+
+        let failed = !write_errors.is_empty();
+        // an ordered command stops at the first failed operation.
+        let executed = match write_errors.keys().next() {
+            Some(first) if ordered => first + 1,
+            _ => self.statements.len(),
+        };
+        let single = self.statements.len() == 1;
+        let counts = &mut outcome.counts;
+        match self.kind {
+            CommandKind::Insert => counts.inserted += n,
+            CommandKind::Update => {
+                counts.upserted += upserted.len() as u64;
+                counts.matched += n.saturating_sub(upserted.len() as u64);
+                counts.modified += count_of(&reply, "nModified");
+            }
+            CommandKind::Delete => counts.deleted += n,
+        }
+        for (index, statement) in self.statements.iter().enumerate().take(executed) {
+            let result = match (write_errors.remove(&index), self.kind) {
+                (Some(message), _) => OpResult {
+                    error: Some(message),
+                    ..Default::default()
+                },
+                (None, CommandKind::Insert) => OpResult {
+                    inserted_id: statement.get("_id").cloned(),
+                    ..Default::default()
+                },
+                (None, CommandKind::Update) => {
+                    let upserted_id = upserted.remove(&index);
+                    let matched = n.saturating_sub(u64::from(upserted_id.is_some()));
+                    OpResult {
+                        matched: single.then_some(matched),
+                        modified: single.then(|| count_of(&reply, "nModified")),
+                        upserted_id,
+                        ..Default::default()
+                    }
+                }
+                (None, CommandKind::Delete) => OpResult {
+                    deleted: single.then_some(n),
+                    ..Default::default()
+                },
+            };
+            outcome.results[self.offset + index] = Some(result);
+        }
+        failed
+    }
+}
+
+/// Get a count in write command reply, it can be Int32 or Int64.
+pub(super) fn count_of(reply: &Document, key: &str) -> u64 {
+    match reply.get(key) {
+        Some(Bson::Int32(n)) => *n as u64,
+        Some(Bson::Int64(n)) => *n as u64,
+        _ => 0,
+    }
+}
+
+/// Get documents with `index` field in write command reply, e.g: `writeErrors` or `upserted`.
+pub(super) fn indexed_docs<'a>(
+    reply: &'a Document,
+    key: &str,
+) -> impl Iterator<Item = (usize, &'a Document)> + 'a {
+    reply
+        .get_array(key)
+        .map(|docs| docs.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|d| {
+            let d = d.as_document()?;
+            let index = match d.get("index")? {
+                Bson::Int32(i) => *i as usize,
+                Bson::Int64(i) => *i as usize,
+                _ => return None,
+            };
+            Some((index, d))
+        })
+}
+
+/// Get messages of `writeErrors` in write command reply, by index of the operation.
+pub(super) fn write_errors(reply: &Document) -> impl Iterator<Item = (usize, String)> + '_ {
+    indexed_docs(reply, "writeErrors").map(|(index, e)| {
+        let message = from_document::<WriteError>(e.clone())
+            .map(|e| e.message)
+            .unwrap_or_else(|_| e.to_string());
+        (index, message)
+    })
+}
+
+/// Get message of `writeConcernError` in write command reply.
+pub(super) fn write_concern_error(reply: &Document) -> Option<String> {
+    let wc_err = reply.get_document("writeConcernError").ok()?;
+    Some(
+        from_document::<WriteConcernError>(wc_err.clone())
+            .map(|e| e.message)
+            .unwrap_or_else(|_| wc_err.to_string()),
+    )
+}
+
+fn to_output(ops: &[WriteOp], outcome: Outcome, span: Span, bson_options: &BsonOptions) -> Value {
+    let opt_int = |v: Option<u64>| match v {
+        None => Value::nothing(span),
+        Some(v) => Value::int(v as i64, span),
+    };
+    let opt_bson = |v: Option<Bson>| match v {
+        None => Value::nothing(span),
+        Some(v) => bson_to_value(v, span, bson_options),
+    };
+    let mut failed = 0;
+    let mut rows = vec![];
+    for (index, (op, result)) in ops.iter().zip(outcome.results).enumerate() {
+        let mut row = record! {
+            "index" => Value::int(index as i64, span),
+            "op" => Value::string(op.name(), span),
+            "coll" => Value::string(&op.coll, span),
+        };
+        let status = match &result {
+            None => "skipped",
+            Some(OpResult { error: Some(_), .. }) => "failed",
+            Some(_) => "ok",
+        };
+        let result = result.unwrap_or_default();
+        failed += u64::from(result.error.is_some());
+        row.push("status", Value::string(status, span));
+        row.push("inserted_id", opt_bson(result.inserted_id));
+        row.push("matched", opt_int(result.matched));
+        row.push("modified", opt_int(result.modified));
+        row.push("upserted_id", opt_bson(result.upserted_id));
+        row.push("deleted", opt_int(result.deleted));
+        row.push(
+            "error",
+            match result.error {
+                None => Value::nothing(span),
+                Some(e) => Value::string(e, span),
+            },
+        );
+        rows.push(Value::record(row, span));
+    }
+    Value::record(
+        record! {
+            "inserted" => Value::int(outcome.counts.inserted as i64, span),
+            "matched" => Value::int(outcome.counts.matched as i64, span),
+            "modified" => Value::int(outcome.counts.modified as i64, span),
+            "upserted" => Value::int(outcome.counts.upserted as i64, span),
+            "deleted" => Value::int(outcome.counts.deleted as i64, span),
+            "failed" => Value::int(failed as i64, span),
+            "write_concern_errors" => Value::list(
+                outcome
+                    .write_concern_errors
+                    .into_iter()
+                    .map(|e| Value::string(e, span))
+                    .collect(),
+                span,
+            ),
+            "results" => Value::list(rows, span),
+        },
+        span,
+    )
+}
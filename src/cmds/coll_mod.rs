@@ -0,0 +1,236 @@
+use super::concern::{with_write_flags, write_concern};
+use super::create_coll::{pipeline_docs, seconds, validation_action, validation_level};
+use super::val_converter::{BsonOptions, doc_to_value, value_to_doc};
+use crate::MongoPlugin;
+use crate::error::mongo_error;
+use mongodb::bson::{Bson, Document, doc, to_bson};
+use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Record, Signature, Spanned, SyntaxShape,
+    Type, Value, engine::ArgType,
+};
+
+pub struct CollMod;
+
+impl SimplePluginCommand for CollMod {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc coll-mod"
+    }
+
+    fn description(&self) -> &str {
+        "modify options of a mongodb collection or view"
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        let sig = Signature::build("mongoc coll-mod")
+            .required("name", SyntaxShape::String, "collection or view name")
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .named(
+                "validator",
+                SyntaxShape::Record(vec![]),
+                "validation rules of documents, e.g: {age: {$gte: 0}} or {$jsonSchema: {...}}",
+                None,
+            )
+            .named(
+                "validation-level",
+                SyntaxShape::String,
+                "which documents are validated, can be off, strict or moderate",
+                None,
+            )
+            .named(
+                "validation-action",
+                SyntaxShape::String,
+                "what to do with invalid documents, can be error or warn",
+                None,
+            )
+            .named(
+                "index",
+                SyntaxShape::OneOf(vec![SyntaxShape::String, SyntaxShape::Record(vec![])]),
+                "index name or key pattern to modify, requires `--expire-after` or `--hidden`",
+                None,
+            )
+            .named(
+                "expire-after",
+                SyntaxShape::Duration,
+                "new TTL of the index",
+                None,
+            )
+            .named(
+                "hidden",
+                SyntaxShape::Boolean,
+                "hide the index from query planner, or unhide it",
+                None,
+            )
+            .named(
+                "view-on",
+                SyntaxShape::String,
+                "new source collection of the view, requires `--pipeline`",
+                None,
+            )
+            .named(
+                "pipeline",
+                SyntaxShape::List(Box::new(SyntaxShape::Record(vec![]))),
+                "new aggregation pipeline of the view",
+                None,
+            )
+            .input_output_type(Type::Nothing, Type::record())
+            .category(Category::Database);
+        with_write_flags(sig)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "only warn documents without name in `students` collection",
+                example: "mongoc coll-mod students --validator {$jsonSchema: {required: [name]}} --validation-action warn",
+                result: None,
+            },
+            Example {
+                description: "remove sessions after 1 hour, by modifying TTL of `lastSeen_1` index",
+                example: "mongoc coll-mod sessions --index lastSeen_1 --expire-after 1hr",
+                result: None,
+            },
+            Example {
+                description: "hide `{age: 1}` index of `students` collection",
+                example: "mongoc coll-mod students --index {age: 1} --hidden true",
+                result: None,
+            },
+            Example {
+                description: "change pipeline of `adults` view",
+                example: "mongoc coll-mod adults --view-on students --pipeline [{$match: {age: {$gte: 21}}}]",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (handle_id, plugin.get_handle(handle_id, call.head)?)
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (handle_id, plugin.get_handle(handle_id, db_handle.span())?)
+            }
+        };
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
+        let name: String = call.req(0)?;
+        let mut command = doc! { "collMod": &name };
+
+        let validator: Option<Record> = call.get_flag("validator")?;
+        if let Some(validator) = validator {
+            command.insert(
+                "validator",
+                value_to_doc(validator, &bson_options.filter())?,
+            );
+        }
+        if let Some(level) = validation_level(call)? {
+            command.insert("validationLevel", to_bson_value(&level)?);
+        }
+        if let Some(action) = validation_action(call)? {
+            command.insert("validationAction", to_bson_value(&action)?);
+        }
+
+        let index: Option<Value> = call.get_flag("index")?;
+        let expire_after: Option<Spanned<i64>> = call.get_flag("expire-after")?;
+        let hidden: Option<bool> = call.get_flag("hidden")?;
+        match index {
+            Some(index) => {
+                let mut index_doc = match index {
+                    Value::Record { val, .. } => {
+                        doc! { "keyPattern": value_to_doc(val.into_owned(), &bson_options)? }
+                    }
+                    other => doc! { "name": other.coerce_into_string()? },
+                };
+                if let Some(expire_after) = expire_after {
+                    index_doc.insert(
+                        "expireAfterSeconds",
+                        seconds(expire_after)?.as_secs() as i64,
+                    );
+                }
+                if let Some(hidden) = hidden {
+                    index_doc.insert("hidden", hidden);
+                }
+                command.insert("index", index_doc);
+            }
+            None => {
+                if let Some(span) = expire_after
+                    .map(|e| e.span)
+                    .or(call.get_flag_value("hidden").map(|h| h.span()))
+                {
+                    return Err(LabeledError::new("index is required")
+                        .with_label("only valid with `--index`", span));
+                }
+            }
+        }
+
+        let view_on: Option<Spanned<String>> = call.get_flag("view-on")?;
+        let pipeline: Option<Vec<Value>> = call.get_flag("pipeline")?;
+        let view_changed = pipeline.is_some();
+        if let Some(view_on) = view_on {
+            if !view_changed {
+                return Err(LabeledError::new("pipeline is required")
+                    .with_label("only valid with `--pipeline`", view_on.span));
+            }
+            command.insert("viewOn", view_on.item);
+        }
+        if let Some(pipeline) = pipeline {
+            command.insert("pipeline", pipeline_docs(pipeline, &bson_options)?);
+        }
+
+        if command.len() == 1 {
+            return Err(LabeledError::new("nothing to modify")
+                .with_label("no option is given", call.head)
+                .with_help("Pass one of `--validator`, `--validation-level`, `--validation-action`, `--index` or `--pipeline`"));
+        }
+        if let Some(wc) = write_concern(call)? {
+            command.insert("writeConcern", to_bson_value(&wc)?);
+        }
+        let reply: Document = db
+            .run_command(command)
+            .run()
+            .map_err(|e| mongo_error(e, call))?;
+        if view_changed {
+            plugin.invalidate_completions(handle_id, Some(&name));
+        }
+
+        Ok(doc_to_value(reply, call.head, &bson_options))
+    }
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Positional(0) => super::get_collection_names_at_handle(plugin, engine, &call),
+            ArgType::Flag(name) if name == "view-on" => {
+                super::get_collection_names_at_handle(plugin, engine, &call)
+            }
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
+
+fn to_bson_value<T: serde::Serialize>(v: &T) -> Result<Bson, LabeledError> {
+    to_bson(v).map_err(|e| LabeledError::new(format!("{e}")))
+}
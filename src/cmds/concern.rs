@@ -0,0 +1,173 @@
+use mongodb::options::{
+    Acknowledgment, CollectionOptions, ReadConcern, ReadPreference, ReadPreferenceOptions,
+    SelectionCriteria, TagSet, WriteConcern,
+};
+use nu_plugin::EvaluatedCall;
+use nu_protocol::{LabeledError, Record, Signature, Spanned, SyntaxShape, Value};
+use std::time::Duration;
+
+/// Add `--read-preference`, `--read-tags`, `--max-staleness` and `--read-concern` flags.
+pub(super) fn with_read_flags(sig: Signature) -> Signature {
+    sig.named(
+        "read-preference",
+        SyntaxShape::String,
+        "read preference, can be primary, primaryPreferred, secondary, secondaryPreferred or nearest",
+        None,
+    )
+    .named(
+        "read-tags",
+        SyntaxShape::List(Box::new(SyntaxShape::Record(vec![]))),
+        "tag sets of read preference, they're checked in order until servers are found",
+        None,
+    )
+    .named(
+        "max-staleness",
+        SyntaxShape::Duration,
+        "max replication lag of secondaries to read from, at least 90sec",
+        None,
+    )
+    .named(
+        "read-concern",
+        SyntaxShape::String,
+        "read concern level, can be local, available, majority, snapshot or linearizable",
+        None,
+    )
+}
+
+/// Add `--write-concern` flag.
+pub(super) fn with_write_flags(sig: Signature) -> Signature {
+    sig.named(
+        "write-concern",
+        SyntaxShape::Record(vec![]),
+        "write concern, e.g: {w: majority, j: true, wtimeout: 5sec}",
+        None,
+    )
+}
+
+/// Get collection options from read and write flags.
+pub(super) fn collection_options(call: &EvaluatedCall) -> Result<CollectionOptions, LabeledError> {
+    let mut options = CollectionOptions::default();
+    options.selection_criteria = read_preference(call)?.map(SelectionCriteria::ReadPreference);
+    options.read_concern = read_concern(call)?;
+    options.write_concern = write_concern(call)?;
+    Ok(options)
+}
+
+pub(super) fn read_preference(
+    call: &EvaluatedCall,
+) -> Result<Option<ReadPreference>, LabeledError> {
+    let mode: Option<Spanned<String>> = call.get_flag("read-preference")?;
+    let tags: Option<Spanned<Vec<Value>>> = call.get_flag("read-tags")?;
+    let max_staleness: Option<Spanned<i64>> = call.get_flag("max-staleness")?;
+    let Some(mode) = mode else {
+        if let Some(span) = tags
+            .as_ref()
+            .map(|t| t.span)
+            .or(max_staleness.as_ref().map(|m| m.span))
+        {
+            return Err(LabeledError::new("read preference is required")
+                .with_label("requires `--read-preference`", span));
+        }
+        return Ok(None);
+    };
+
+    let mut read_options = ReadPreferenceOptions::default();
+    if let Some(tags) = tags {
+        let mut tag_sets = vec![];
+        for tag_set in tags.item {
+            tag_sets.push(to_tag_set(tag_set.into_record()?)?);
+        }
+        read_options.tag_sets = Some(tag_sets);
+    }
+    if let Some(max_staleness) = max_staleness {
+        let nanos = u64::try_from(max_staleness.item).map_err(|_| {
+            LabeledError::new("get invalid duration")
+                .with_label("can't be negative", max_staleness.span)
+        })?;
+        read_options.max_staleness = Some(Duration::from_nanos(nanos));
+    }
+    let has_options = read_options.tag_sets.is_some() || read_options.max_staleness.is_some();
+    let options = Some(read_options);
+    Ok(Some(match mode.item.as_str() {
+        "primary" if has_options => {
+            return Err(LabeledError::new("invalid read preference").with_label(
+                "tag sets and max staleness can't be used with primary",
+                mode.span,
+            ));
+        }
+        "primary" => ReadPreference::Primary,
+        "primaryPreferred" => ReadPreference::PrimaryPreferred { options },
+        "secondary" => ReadPreference::Secondary { options },
+        "secondaryPreferred" => ReadPreference::SecondaryPreferred { options },
+        "nearest" => ReadPreference::Nearest { options },
+        _ => {
+            return Err(LabeledError::new("invalid read preference").with_label(
+                "should be one of primary, primaryPreferred, secondary, secondaryPreferred, nearest",
+                mode.span,
+            ));
+        }
+    }))
+}
+
+fn to_tag_set(record: Record) -> Result<TagSet, LabeledError> {
+    let mut tag_set = TagSet::new();
+    for (k, v) in record {
+        tag_set.insert(k, v.coerce_into_string()?);
+    }
+    Ok(tag_set)
+}
+
+pub(super) fn read_concern(call: &EvaluatedCall) -> Result<Option<ReadConcern>, LabeledError> {
+    let level: Option<Spanned<String>> = call.get_flag("read-concern")?;
+    let Some(level) = level else {
+        return Ok(None);
+    };
+    Ok(Some(match level.item.as_str() {
+        "local" => ReadConcern::local(),
+        "available" => ReadConcern::available(),
+        "majority" => ReadConcern::majority(),
+        "snapshot" => ReadConcern::snapshot(),
+        "linearizable" => ReadConcern::linearizable(),
+        _ => {
+            return Err(LabeledError::new("invalid read concern").with_label(
+                "should be one of local, available, majority, snapshot, linearizable",
+                level.span,
+            ));
+        }
+    }))
+}
+
+pub(super) fn write_concern(call: &EvaluatedCall) -> Result<Option<WriteConcern>, LabeledError> {
+    let concern: Option<Spanned<Record>> = call.get_flag("write-concern")?;
+    let Some(concern) = concern else {
+        return Ok(None);
+    };
+    let mut write_concern = WriteConcern::default();
+    for (k, v) in concern.item {
+        let span = v.span();
+        match (k.as_str(), v) {
+            ("w", Value::Int { val, .. }) => {
+                let nodes = u32::try_from(val).map_err(|_| {
+                    LabeledError::new("invalid write concern").with_label("can't be negative", span)
+                })?;
+                write_concern.w = Some(Acknowledgment::Nodes(nodes));
+            }
+            ("w", Value::String { val, .. }) => write_concern.w = Some(Acknowledgment::from(val)),
+            ("j", Value::Bool { val, .. }) => write_concern.journal = Some(val),
+            ("wtimeout", Value::Duration { val, .. }) if val >= 0 => {
+                write_concern.w_timeout = Some(Duration::from_nanos(val as u64))
+            }
+            ("w" | "j" | "wtimeout", _) => {
+                return Err(LabeledError::new("invalid write concern").with_label(
+                    "`w` should be int or string, `j` should be bool, `wtimeout` should be positive duration",
+                    span,
+                ));
+            }
+            _ => {
+                return Err(LabeledError::new("invalid write concern")
+                    .with_label(format!("unknown field `{k}`"), span));
+            }
+        }
+    }
+    Ok(Some(write_concern))
+}
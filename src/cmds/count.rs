@@ -1,10 +1,13 @@
-use super::val_converter::value_to_doc;
+use super::concern::{collection_options, with_read_flags};
+use super::query_dsl::{query_filter, with_where_flag};
+use super::val_converter::{BsonOptions, value_to_doc};
 use crate::MongoPlugin;
+use crate::error::mongo_error;
 use mongodb::bson::Document;
 use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
 use nu_protocol::{
-    Category, DynamicSuggestion, Example, LabeledError, Record, Signature, Spanned, SyntaxShape,
-    Type, Value, engine::ArgType,
+    Category, DynamicSuggestion, Example, LabeledError, Record, Signature, SyntaxShape, Type,
+    Value, engine::ArgType,
 };
 
 pub struct Count;
@@ -25,7 +28,7 @@ impl SimplePluginCommand for Count {
     }
 
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("mongoc count")
+        with_where_flag(with_read_flags(Signature::build("mongoc count")))
             .optional("query object", SyntaxShape::Record(vec![]), "query object")
             .required_named(
                 "collection",
@@ -35,15 +38,15 @@ impl SimplePluginCommand for Count {
             )
             .named(
                 "db-handle",
-                SyntaxShape::Int,
-                "database handle, can get from `mongoc list`",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
                 Some('d'),
             )
             .input_output_type(Type::Nothing, Type::record())
             .category(Category::Database)
     }
 
-    fn examples(&self) -> Vec<Example> {
+    fn examples(&self) -> Vec<Example<'_>> {
         vec![
             Example {
                 description: "count students with age `19`, in a `students` collection",
@@ -60,44 +63,62 @@ impl SimplePluginCommand for Count {
                 example: "mongoc count {name: John} -d 0 -c teachers -s {\"age\": -1}",
                 result: None,
             },
+            Example {
+                description: "count adult students whose name starts with `J`",
+                example: "mongoc count -c students --where {|r| $r.age >= 18 and $r.name =~ '^J'}",
+                result: None,
+            },
         ]
     }
 
     fn run(
         &self,
         plugin: &MongoPlugin,
-        _engine: &nu_plugin::EngineInterface,
+        engine: &nu_plugin::EngineInterface,
         call: &nu_plugin::EvaluatedCall,
         _input: &Value,
     ) -> Result<Value, LabeledError> {
-        let db_handle: Option<Spanned<i64>> = call.get_flag("db-handle")?;
-        let db = match db_handle {
-            None => plugin.get_handle(plugin.get_current()?, call.head)?,
-            Some(db_handle) => plugin.get_handle(db_handle.item as u8, db_handle.span)?,
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (handle_id, plugin.get_handle(handle_id, call.head)?)
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (handle_id, plugin.get_handle(handle_id, db_handle.span())?)
+            }
         };
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
         let coll: String = call
             .get_flag("collection")?
             .expect("already check existed.");
         let query: Record = call.opt(0)?.unwrap_or_default();
-        let coll = db.collection::<Document>(&coll);
-        let counts = coll.count_documents(value_to_doc(query)?);
-        let result = counts
-            .run()
-            .map_err(|e| LabeledError::new(format!("{e}")))?;
+        let coll = db.collection_with_options::<Document>(&coll, collection_options(call)?);
+        let counts = coll.count_documents(query_filter(
+            engine,
+            call,
+            value_to_doc(query, &bson_options.filter())?,
+            &bson_options,
+        )?);
+        let result = counts.run().map_err(|e| mongo_error(e, call))?;
         Ok(Value::int(result as i64, call.head))
     }
+    #[allow(deprecated)]
     fn get_dynamic_completion(
         &self,
         plugin: &Self::Plugin,
-        _engine: &EngineInterface,
-        _call: DynamicCompletionCall,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
         arg_type: ArgType,
         _experimental: nu_protocol::engine::ExperimentalMarker,
     ) -> Option<Vec<DynamicSuggestion>> {
         match arg_type {
             ArgType::Flag(name) if name == "collection" => {
-                super::get_collection_names_at_current_handle(plugin)
+                super::get_collection_names_at_handle(plugin, engine, &call)
             }
+            ArgType::Positional(0) => super::get_field_names_at_handle(plugin, engine, &call),
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
             _ => None,
         }
     }
@@ -0,0 +1,310 @@
+use super::concern::{with_write_flags, write_concern};
+use super::val_converter::{BsonOptions, value_to_doc};
+use crate::MongoPlugin;
+use crate::error::mongo_error;
+use mongodb::bson::{Document, from_document};
+use mongodb::options::{ClusteredIndex, ValidationAction, ValidationLevel};
+use nu_plugin::{DynamicCompletionCall, EngineInterface, EvaluatedCall, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Record, Signature, Spanned, SyntaxShape,
+    Type, Value, engine::ArgType,
+};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+pub struct CreateColl;
+
+impl SimplePluginCommand for CreateColl {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc create-coll"
+    }
+
+    fn description(&self) -> &str {
+        "create a mongodb collection or view"
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        let sig = Signature::build("mongoc create-coll")
+            .required("name", SyntaxShape::String, "collection name")
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .switch("capped", "create a capped collection, requires `--size`", None)
+            .named(
+                "size",
+                SyntaxShape::Filesize,
+                "max size of capped collection",
+                None,
+            )
+            .named(
+                "max",
+                SyntaxShape::Int,
+                "max number of documents in capped collection",
+                None,
+            )
+            .named(
+                "validator",
+                SyntaxShape::Record(vec![]),
+                "validation rules of documents, e.g: {age: {$gte: 0}} or {$jsonSchema: {...}}",
+                None,
+            )
+            .named(
+                "validation-level",
+                SyntaxShape::String,
+                "which documents are validated, can be off, strict or moderate",
+                None,
+            )
+            .named(
+                "validation-action",
+                SyntaxShape::String,
+                "what to do with invalid documents, can be error or warn",
+                None,
+            )
+            .named(
+                "timeseries",
+                SyntaxShape::Record(vec![]),
+                "create a time-series collection, e.g: {timeField: ts, metaField: sensor, granularity: minutes}",
+                None,
+            )
+            .named(
+                "expire-after",
+                SyntaxShape::Duration,
+                "remove documents of time-series or clustered collection after the duration",
+                None,
+            )
+            .switch(
+                "clustered-index",
+                "create a clustered collection, which is clustered by `_id`",
+                None,
+            )
+            .named(
+                "collation",
+                SyntaxShape::Record(vec![]),
+                "default collation of the collection, e.g: {locale: en, strength: 2}",
+                None,
+            )
+            .named(
+                "view-on",
+                SyntaxShape::String,
+                "create a view on the given collection, instead of a collection",
+                None,
+            )
+            .named(
+                "pipeline",
+                SyntaxShape::List(Box::new(SyntaxShape::Record(vec![]))),
+                "aggregation pipeline of the view, requires `--view-on`",
+                None,
+            )
+            .input_output_type(Type::Nothing, Type::Nothing)
+            .category(Category::Database);
+        with_write_flags(sig)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "create `students` collection",
+                example: "mongoc create-coll students",
+                result: None,
+            },
+            Example {
+                description: "create `logs` capped collection, which keeps at most 1000 documents",
+                example: "mongoc create-coll logs --capped --size 1MB --max 1000",
+                result: None,
+            },
+            Example {
+                description: "create `students` collection, which rejects documents without name",
+                example: "mongoc create-coll students --validator {$jsonSchema: {required: [name]}}",
+                result: None,
+            },
+            Example {
+                description: "create `weather` time-series collection, which removes documents after 30 days",
+                example: "mongoc create-coll weather --timeseries {timeField: ts, metaField: sensor, granularity: hours} --expire-after 30day",
+                result: None,
+            },
+            Example {
+                description: "create `adults` view on `students` collection",
+                example: "mongoc create-coll adults --view-on students --pipeline [{$match: {age: {$gte: 18}}}]",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (handle_id, plugin.get_handle(handle_id, call.head)?)
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (handle_id, plugin.get_handle(handle_id, db_handle.span())?)
+            }
+        };
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
+        let name: String = call.req(0)?;
+        let capped = call.has_flag("capped")?;
+        let size: Option<Spanned<i64>> = call.get_flag("size")?;
+        let max: Option<Spanned<i64>> = call.get_flag("max")?;
+        if !capped && let Some(span) = size.as_ref().or(max.as_ref()).map(|v| v.span) {
+            return Err(LabeledError::new("not a capped collection")
+                .with_label("only valid with `--capped`", span));
+        }
+        if capped && size.is_none() {
+            return Err(LabeledError::new("size is required")
+                .with_label("capped collection requires `--size`", call.head));
+        }
+        let pipeline: Option<Spanned<Vec<Value>>> = call.get_flag("pipeline")?;
+        let view_on: Option<String> = call.get_flag("view-on")?;
+        if view_on.is_none()
+            && let Some(pipeline) = &pipeline
+        {
+            return Err(LabeledError::new("not a view")
+                .with_label("only valid with `--view-on`", pipeline.span));
+        }
+
+        let mut create = db.create_collection(&name);
+        if capped {
+            create = create.capped(true);
+        }
+        if call.has_flag("clustered-index")? {
+            create = create.clustered_index(ClusteredIndex::default());
+        }
+        if let Some(wc) = write_concern(call)? {
+            create = create.write_concern(wc);
+        }
+        if let Some(size) = size {
+            create = create.size(non_negative(size)?);
+        }
+        if let Some(max) = max {
+            create = create.max(non_negative(max)?);
+        }
+        let validator: Option<Record> = call.get_flag("validator")?;
+        if let Some(validator) = validator {
+            create = create.validator(value_to_doc(validator, &bson_options.filter())?);
+        }
+        if let Some(level) = validation_level(call)? {
+            create = create.validation_level(level);
+        }
+        if let Some(action) = validation_action(call)? {
+            create = create.validation_action(action);
+        }
+        if let Some(timeseries) = record_flag(call, "timeseries", &bson_options)? {
+            create = create.timeseries(timeseries);
+        }
+        if let Some(collation) = record_flag(call, "collation", &bson_options)? {
+            create = create.collation(collation);
+        }
+        let expire_after: Option<Spanned<i64>> = call.get_flag("expire-after")?;
+        if let Some(expire_after) = expire_after {
+            create = create.expire_after_seconds(seconds(expire_after)?);
+        }
+        if let Some(view_on) = view_on {
+            create = create.view_on(view_on);
+        }
+        if let Some(pipeline) = pipeline {
+            create = create.pipeline(pipeline_docs(pipeline.item, &bson_options)?);
+        }
+        create.run().map_err(|e| mongo_error(e, call))?;
+        plugin.invalidate_completions(handle_id, None);
+
+        Ok(Value::nothing(call.head))
+    }
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "view-on" => {
+                super::get_collection_names_at_handle(plugin, engine, &call)
+            }
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
+
+pub(super) fn validation_level(
+    call: &EvaluatedCall,
+) -> Result<Option<ValidationLevel>, LabeledError> {
+    let level: Option<Spanned<String>> = call.get_flag("validation-level")?;
+    let Some(level) = level else {
+        return Ok(None);
+    };
+    match level.item.as_str() {
+        "off" => Ok(Some(ValidationLevel::Off)),
+        "strict" => Ok(Some(ValidationLevel::Strict)),
+        "moderate" => Ok(Some(ValidationLevel::Moderate)),
+        _ => Err(LabeledError::new("unknown validation level")
+            .with_label("should be one of off, strict, moderate", level.span)),
+    }
+}
+
+pub(super) fn validation_action(
+    call: &EvaluatedCall,
+) -> Result<Option<ValidationAction>, LabeledError> {
+    let action: Option<Spanned<String>> = call.get_flag("validation-action")?;
+    let Some(action) = action else {
+        return Ok(None);
+    };
+    match action.item.as_str() {
+        "error" => Ok(Some(ValidationAction::Error)),
+        "warn" => Ok(Some(ValidationAction::Warn)),
+        _ => Err(LabeledError::new("unknown validation action")
+            .with_label("should be one of error, warn", action.span)),
+    }
+}
+
+pub(super) fn pipeline_docs(
+    stages: Vec<Value>,
+    bson_options: &BsonOptions,
+) -> Result<Vec<Document>, LabeledError> {
+    stages
+        .into_iter()
+        .map(|stage| value_to_doc(stage.into_record()?, bson_options))
+        .collect()
+}
+
+/// Convert a duration flag into whole seconds, which is used by TTL options.
+pub(super) fn seconds(d: Spanned<i64>) -> Result<Duration, LabeledError> {
+    Ok(Duration::from_secs(non_negative(d)? / 1_000_000_000))
+}
+
+fn non_negative(v: Spanned<i64>) -> Result<u64, LabeledError> {
+    u64::try_from(v.item).map_err(|_| {
+        LabeledError::new("get invalid number").with_label("can't be negative", v.span)
+    })
+}
+
+/// Convert a record flag into driver options, e.g: `--timeseries` or `--collation`.
+fn record_flag<T: DeserializeOwned>(
+    call: &EvaluatedCall,
+    name: &str,
+    bson_options: &BsonOptions,
+) -> Result<Option<T>, LabeledError> {
+    let Some(value) = call.get_flag_value(name) else {
+        return Ok(None);
+    };
+    let span = value.span();
+    let doc = value_to_doc(value.into_record()?, bson_options)?;
+    from_document(doc)
+        .map(Some)
+        .map_err(|e| LabeledError::new(format!("invalid {name}")).with_label(format!("{e}"), span))
+}
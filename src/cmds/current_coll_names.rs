@@ -1,22 +1,32 @@
+use super::handle_from_call;
 use crate::MongoPlugin;
-use nu_protocol::{DynamicSuggestion, Span};
+use crate::completion_cache::{CacheKey, CompletionConfig};
+use nu_plugin::{DynamicCompletionCall, EngineInterface};
+use nu_protocol::DynamicSuggestion;
 
-pub fn get_collection_names_at_current_handle(
+/// Get collection names at the handle given by `db-handle` flag, or current handle.
+pub fn get_collection_names_at_handle(
     plugin: &MongoPlugin,
+    engine: &EngineInterface,
+    call: &DynamicCompletionCall,
 ) -> Option<Vec<DynamicSuggestion>> {
-    let current_handle = plugin.get_current();
-    if let Ok(current_handle) = current_handle {
-        get_collection_names(current_handle, plugin)
-    } else {
-        None
-    }
+    get_collection_names(handle_from_call(plugin, call)?, plugin, engine)
 }
 
-fn get_collection_names(handle: u8, plugin: &MongoPlugin) -> Option<Vec<DynamicSuggestion>> {
+fn get_collection_names(
+    handle: u32,
+    plugin: &MongoPlugin,
+    engine: &EngineInterface,
+) -> Option<Vec<DynamicSuggestion>> {
+    let config = CompletionConfig::from_engine(engine);
+    let db = plugin
+        .get_completion_handle(handle, config.server_timeout)
+        .ok()?;
     plugin
-        .get_handle(handle, Span::unknown())
-        .map(|db| db.list_collection_names().run().ok())
-        .unwrap_or(None)
+        .completions
+        .get_or_refresh(CacheKey::CollectionNames(handle), &config, move || {
+            db.list_collection_names().run().ok()
+        })
         .map(|coll_names| {
             coll_names
                 .into_iter()
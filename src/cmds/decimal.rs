@@ -0,0 +1,64 @@
+use crate::MongoPlugin;
+use mongodb::bson::Decimal128;
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{Category, Example, LabeledError, Signature, SyntaxShape, Type, Value, record};
+
+pub struct Decimal;
+
+impl SimplePluginCommand for Decimal {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc decimal"
+    }
+
+    fn description(&self) -> &str {
+        "make a Decimal128 literal, which can be used in query and documents"
+    }
+
+    fn extra_description(&self) -> &str {
+        "The literal is a record like `{$numberDecimal: \"0.1\"}`.  Pass the number as string to keep exact digits, floats may be rounded."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc decimal")
+            .required(
+                "number",
+                SyntaxShape::OneOf(vec![SyntaxShape::String, SyntaxShape::Number]),
+                "the decimal number",
+            )
+            .input_output_type(Type::Nothing, Type::record())
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![Example {
+            description: "find orders whose price is greater than 9.99",
+            example: "mongoc find {price: {$gt: (mongoc decimal \"9.99\")}} -c orders",
+            result: None,
+        }]
+    }
+
+    fn run(
+        &self,
+        _plugin: &MongoPlugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let number: Value = call.req(0)?;
+        let span = number.span();
+        let text = match number {
+            Value::Int { val, .. } => val.to_string(),
+            Value::Float { val, .. } => val.to_string(),
+            other => other.coerce_into_string()?,
+        };
+        let decimal = text
+            .parse::<Decimal128>()
+            .map_err(|e| LabeledError::new("invalid decimal").with_label(format!("{e}"), span))?;
+        Ok(Value::record(
+            record! { "$numberDecimal" => Value::string(decimal.to_string(), call.head) },
+            call.head,
+        ))
+    }
+}
@@ -1,9 +1,10 @@
 use crate::MongoPlugin;
+use crate::error::mongo_error;
 use mongodb::bson::Document;
 use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
 use nu_protocol::{
-    Category, DynamicSuggestion, Example, LabeledError, Signature, Spanned, SyntaxShape, Type,
-    Value, engine::ArgType,
+    Category, DynamicSuggestion, Example, LabeledError, Signature, SyntaxShape, Type, Value,
+    engine::ArgType,
 };
 
 pub struct Drop;
@@ -29,15 +30,15 @@ impl SimplePluginCommand for Drop {
             )
             .named(
                 "db-handle",
-                SyntaxShape::Int,
-                "database handle, can get from `mongoc list`",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
                 Some('d'),
             )
             .input_output_type(Type::Nothing, Type::Nothing)
             .category(Category::Database)
     }
 
-    fn examples(&self) -> Vec<Example> {
+    fn examples(&self) -> Vec<Example<'_>> {
         vec![
             Example {
                 description: "drop `students` collection",
@@ -59,34 +60,41 @@ impl SimplePluginCommand for Drop {
         call: &nu_plugin::EvaluatedCall,
         _input: &Value,
     ) -> Result<Value, LabeledError> {
-        let db_handle: Option<Spanned<i64>> = call.get_flag("db-handle")?;
-        let db = match db_handle {
-            None => plugin.get_handle(plugin.get_current()?, call.head)?,
-            Some(db_handle) => plugin.get_handle(db_handle.item as u8, db_handle.span)?,
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (handle_id, plugin.get_handle(handle_id, call.head)?)
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (handle_id, plugin.get_handle(handle_id, db_handle.span())?)
+            }
         };
-        let coll: String = call
+        let coll_name: String = call
             .get_flag("collection")?
             .expect("already check existed.");
-        let coll = db.collection::<Document>(&coll);
+        let coll = db.collection::<Document>(&coll_name);
         let drop_cmd = coll.drop();
-        drop_cmd
-            .run()
-            .map_err(|e| LabeledError::new(format!("{e}")))?;
+        drop_cmd.run().map_err(|e| mongo_error(e, call))?;
+        plugin.invalidate_completions(handle_id, Some(&coll_name));
 
         Ok(Value::nothing(call.head))
     }
+    #[allow(deprecated)]
     fn get_dynamic_completion(
         &self,
         plugin: &Self::Plugin,
-        _engine: &EngineInterface,
-        _call: DynamicCompletionCall,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
         arg_type: ArgType,
         _experimental: nu_protocol::engine::ExperimentalMarker,
     ) -> Option<Vec<DynamicSuggestion>> {
         match arg_type {
             ArgType::Flag(name) if name == "collection" => {
-                super::get_collection_names_at_current_handle(plugin)
+                super::get_collection_names_at_handle(plugin, engine, &call)
             }
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
             _ => None,
         }
     }
@@ -1,9 +1,11 @@
+use super::concern::{collection_options, with_read_flags};
 use crate::MongoPlugin;
+use crate::error::mongo_error;
 use mongodb::bson::Document;
 use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
 use nu_protocol::{
-    Category, DynamicSuggestion, Example, LabeledError, Signature, Spanned, SyntaxShape, Type,
-    Value, engine::ArgType,
+    Category, DynamicSuggestion, Example, LabeledError, Signature, SyntaxShape, Type, Value,
+    engine::ArgType,
 };
 
 pub struct Estimated;
@@ -20,7 +22,7 @@ impl SimplePluginCommand for Estimated {
     }
 
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("mongoc estimated")
+        with_read_flags(Signature::build("mongoc estimated"))
             .required_named(
                 "collection",
                 SyntaxShape::String,
@@ -29,15 +31,15 @@ impl SimplePluginCommand for Estimated {
             )
             .named(
                 "db-handle",
-                SyntaxShape::Int,
-                "database handle, can get from `mongoc list`",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
                 Some('d'),
             )
             .input_output_type(Type::Nothing, Type::record())
             .category(Category::Database)
     }
 
-    fn examples(&self) -> Vec<Example> {
+    fn examples(&self) -> Vec<Example<'_>> {
         vec![
             Example {
                 description: "estimated students count with age `19`, in a `students` collection",
@@ -64,34 +66,36 @@ impl SimplePluginCommand for Estimated {
         call: &nu_plugin::EvaluatedCall,
         _input: &Value,
     ) -> Result<Value, LabeledError> {
-        let db_handle: Option<Spanned<i64>> = call.get_flag("db-handle")?;
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
         let db = match db_handle {
             None => plugin.get_handle(plugin.get_current()?, call.head)?,
-            Some(db_handle) => plugin.get_handle(db_handle.item as u8, db_handle.span)?,
+            Some(db_handle) => {
+                plugin.get_handle(plugin.resolve_handle_id(&db_handle)?, db_handle.span())?
+            }
         };
         let coll: String = call
             .get_flag("collection")?
             .expect("already check existed.");
-        let coll = db.collection::<Document>(&coll);
+        let coll = db.collection_with_options::<Document>(&coll, collection_options(call)?);
         let estimated = coll.estimated_document_count();
-        let result = estimated
-            .run()
-            .map_err(|e| LabeledError::new(format!("{e}")))?;
+        let result = estimated.run().map_err(|e| mongo_error(e, call))?;
         Ok(Value::int(result as i64, call.head))
     }
 
+    #[allow(deprecated)]
     fn get_dynamic_completion(
         &self,
         plugin: &Self::Plugin,
-        _engine: &EngineInterface,
-        _call: DynamicCompletionCall,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
         arg_type: ArgType,
         _experimental: nu_protocol::engine::ExperimentalMarker,
     ) -> Option<Vec<DynamicSuggestion>> {
         match arg_type {
             ArgType::Flag(name) if name == "collection" => {
-                super::get_collection_names_at_current_handle(plugin)
+                super::get_collection_names_at_handle(plugin, engine, &call)
             }
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
             _ => None,
         }
     }
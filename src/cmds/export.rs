@@ -0,0 +1,350 @@
+use super::concern::{collection_options, with_read_flags};
+use super::val_converter::{BsonOptions, value_to_doc};
+use crate::MongoPlugin;
+use crate::error::{mongo_error, mongo_error_at};
+use mongodb::bson::{Bson, Document};
+use mongodb::options::FindOptions;
+use nu_plugin::{DynamicCompletionCall, EngineInterface, EvaluatedCall, PluginCommand};
+use nu_protocol::{
+    ByteStream, ByteStreamType, Category, DynamicSuggestion, Example, LabeledError, PipelineData,
+    Record, ShellError, Signature, Spanned, SyntaxShape, Type, Value, engine::ArgType,
+};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+pub struct Export;
+
+#[derive(Clone, Copy)]
+enum ExportFormat {
+    /// A json array of extended json documents.
+    Ejson,
+    /// One extended json document per line.
+    Ndjson,
+    /// Concatenated raw bson documents, compatible with mongodump.
+    Bson,
+}
+
+impl ExportFormat {
+    fn parse(format: &Spanned<String>) -> Result<Self, LabeledError> {
+        match format.item.as_str() {
+            "ejson" | "json" => Ok(Self::Ejson),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            "bson" => Ok(Self::Bson),
+            _ => Err(LabeledError::new("invalid export format")
+                .with_label("unknown format", format.span)
+                .with_help("supported formats are `ejson`, `ndjson` and `bson`")),
+        }
+    }
+
+    /// Guess export format by file extension, fallback to ndjson.
+    fn from_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::Ejson,
+            Some("bson") => Self::Bson,
+            _ => Self::Ndjson,
+        }
+    }
+
+    fn stream_type(self) -> ByteStreamType {
+        match self {
+            Self::Bson => ByteStreamType::Binary,
+            Self::Ejson | Self::Ndjson => ByteStreamType::String,
+        }
+    }
+}
+
+/// Serialize documents one by one into the given export format.
+struct DocWriter {
+    format: ExportFormat,
+    canonical: bool,
+    written: u64,
+}
+
+impl DocWriter {
+    fn begin(&self, buf: &mut Vec<u8>) {
+        if let ExportFormat::Ejson = self.format {
+            buf.push(b'[');
+        }
+    }
+
+    fn write(&mut self, doc: Document, buf: &mut Vec<u8>) -> Result<(), String> {
+        match self.format {
+            ExportFormat::Bson => doc.to_writer(&mut *buf).map_err(|e| format!("{e}"))?,
+            ExportFormat::Ejson | ExportFormat::Ndjson => {
+                if let ExportFormat::Ejson = self.format {
+                    if self.written > 0 {
+                        buf.push(b',');
+                    }
+                    buf.push(b'\n');
+                }
+                let json = if self.canonical {
+                    Bson::Document(doc).into_canonical_extjson()
+                } else {
+                    Bson::Document(doc).into_relaxed_extjson()
+                };
+                serde_json::to_writer(&mut *buf, &json).map_err(|e| format!("{e}"))?;
+                if let ExportFormat::Ndjson = self.format {
+                    buf.push(b'\n');
+                }
+            }
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(&self, buf: &mut Vec<u8>) {
+        if let ExportFormat::Ejson = self.format {
+            buf.extend_from_slice(b"\n]\n");
+        }
+    }
+}
+
+impl PluginCommand for Export {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc export"
+    }
+
+    fn description(&self) -> &str {
+        "export mongodb documents to extended json, ndjson or bson"
+    }
+
+    fn extra_description(&self) -> &str {
+        "Documents are written to the given output file, or returned as a byte stream if no output file is given. BSON types are kept through extended json, or raw bson which is compatible with mongodump."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        with_read_flags(Signature::build("mongoc export"))
+            .optional("query object", SyntaxShape::Record(vec![]), "query object")
+            .required_named(
+                "collection",
+                SyntaxShape::String,
+                "collection name",
+                Some('c'),
+            )
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .named(
+                "format",
+                SyntaxShape::String,
+                "export format, can be `ejson`, `ndjson` or `bson`, default is guessed from output file extension, or `ndjson`",
+                Some('f'),
+            )
+            .switch(
+                "canonical",
+                "use canonical extended json rather than relaxed extended json",
+                None,
+            )
+            .named(
+                "output",
+                SyntaxShape::Filepath,
+                "output file, returns a byte stream if not given",
+                Some('o'),
+            )
+            .named(
+                "projection",
+                SyntaxShape::Record(vec![]),
+                "projection option",
+                Some('p'),
+            )
+            .named(
+                "sort",
+                SyntaxShape::Record(vec![]),
+                "sort option",
+                Some('s'),
+            )
+            .named(
+                "limit",
+                SyntaxShape::Int,
+                "limit rows to export, default is no limit",
+                Some('l'),
+            )
+            .input_output_types(vec![
+                (Type::Nothing, Type::String),
+                (Type::Nothing, Type::Binary),
+                (Type::Nothing, Type::Int),
+            ])
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "export `students` collection to a ndjson file",
+                example: "mongoc export -c students -o students.ndjson",
+                result: None,
+            },
+            Example {
+                description: "dump `students` with age `19` to a mongodump compatible bson file",
+                example: "mongoc export {age: 19} -c students -f bson -o students.bson",
+                result: None,
+            },
+            Example {
+                description: "export names of the 10 oldest teachers as canonical extended json",
+                example: "mongoc export -c teachers -f ejson --canonical -p {name: 1} -s {age: -1} -l 10",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &EngineInterface,
+        call: &EvaluatedCall,
+        _input: PipelineData,
+    ) -> Result<PipelineData, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (handle_id, plugin.get_handle(handle_id, call.head)?)
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (handle_id, plugin.get_handle(handle_id, db_handle.span())?)
+            }
+        };
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
+        let coll: String = call
+            .get_flag("collection")?
+            .expect("already check existed.");
+        let output: Option<Spanned<String>> = call.get_flag("output")?;
+        let format: Option<Spanned<String>> = call.get_flag("format")?;
+        let format = match (format, &output) {
+            (Some(format), _) => ExportFormat::parse(&format)?,
+            (None, Some(output)) => ExportFormat::from_path(&output.item),
+            (None, None) => ExportFormat::Ndjson,
+        };
+        let limit: Option<Spanned<i64>> = call.get_flag("limit")?;
+        if let Some(limit) = &limit
+            && limit.item.is_negative()
+        {
+            return Err(
+                LabeledError::new("get invalid number").with_label("can't be negative", limit.span)
+            );
+        }
+        let query: Record = call.opt(0)?.unwrap_or_default();
+        let projection: Option<Record> = call.get_flag("projection")?;
+        let sort_options: Option<Record> = call.get_flag("sort")?;
+        let options = FindOptions::builder()
+            .projection(
+                projection
+                    .map(|v| value_to_doc(v, &bson_options))
+                    .transpose()?,
+            )
+            .sort(
+                sort_options
+                    .map(|v| value_to_doc(v, &bson_options))
+                    .transpose()?,
+            )
+            .limit(limit.map(|l| l.item))
+            .build();
+        let cursor = db
+            .collection_with_options::<Document>(&coll, collection_options(call)?)
+            .find(value_to_doc(query, &bson_options.filter())?)
+            .with_options(options)
+            .run()
+            .map_err(|e| mongo_error(e, call))?;
+        let mut writer = DocWriter {
+            format,
+            canonical: call.has_flag("canonical")?,
+            written: 0,
+        };
+
+        match output {
+            Some(output) => {
+                let path = Path::new(&engine.get_current_dir()?).join(&output.item);
+                let file = std::fs::File::create(&path).map_err(|e| {
+                    LabeledError::new(format!("{e}")).with_label("can't create file", output.span)
+                })?;
+                let mut file = BufWriter::new(file);
+                let mut buf = vec![];
+                writer.begin(&mut buf);
+                for doc in cursor {
+                    let doc = doc.map_err(|e| mongo_error(e, call))?;
+                    writer
+                        .write(doc, &mut buf)
+                        .map_err(|e| LabeledError::new(e).with_label("can't export", call.head))?;
+                    file.write_all(&buf).map_err(|e| {
+                        LabeledError::new(format!("{e}")).with_label("can't write", output.span)
+                    })?;
+                    buf.clear();
+                }
+                writer.finish(&mut buf);
+                file.write_all(&buf)
+                    .and_then(|_| file.flush())
+                    .map_err(|e| {
+                        LabeledError::new(format!("{e}")).with_label("can't write", output.span)
+                    })?;
+                Ok(PipelineData::Value(
+                    Value::int(writer.written as i64, call.head),
+                    None,
+                ))
+            }
+            None => {
+                let span = call.head;
+                let mut cursor = Some(cursor);
+                let mut started = false;
+                // the closure signature is required by `ByteStream::from_fn`.
+                #[allow(clippy::result_large_err)]
+                let stream = ByteStream::from_fn(
+                    span,
+                    engine.signals().clone(),
+                    format.stream_type(),
+                    move |buf| {
+                        if !started {
+                            writer.begin(buf);
+                            started = true;
+                        }
+                        match cursor.as_mut().and_then(|c| c.next()) {
+                            Some(Ok(doc)) => {
+                                writer.write(doc, buf).map_err(|e| {
+                                    ShellError::from(
+                                        LabeledError::new(e).with_label("can't export", span),
+                                    )
+                                })?;
+                                Ok(true)
+                            }
+                            Some(Err(e)) => Err(ShellError::from(mongo_error_at(e, span))),
+                            None => {
+                                if cursor.take().is_some() {
+                                    writer.finish(buf);
+                                }
+                                Ok(false)
+                            }
+                        }
+                    },
+                );
+                Ok(PipelineData::ByteStream(stream, None))
+            }
+        }
+    }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "collection" => {
+                super::get_collection_names_at_handle(plugin, engine, &call)
+            }
+            ArgType::Flag(name) if name == "sort" || name == "projection" => {
+                super::get_field_names_at_handle(plugin, engine, &call)
+            }
+            ArgType::Positional(0) => super::get_field_names_at_handle(plugin, engine, &call),
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use super::handle_from_call;
+use crate::MongoPlugin;
+use crate::completion_cache::{CacheKey, CompletionConfig};
+use mongodb::bson::{Bson, Document, doc};
+use mongodb::sync::Database;
+use nu_plugin::{DynamicCompletionCall, EngineInterface};
+use nu_protocol::DynamicSuggestion;
+use std::collections::BTreeSet;
+
+/// How many documents to sample for field names completion.
+const SAMPLE_SIZE: i64 = 100;
+
+/// Get field names of the collection given by `collection` flag, at the handle given by
+/// `db-handle` flag, or current handle.
+pub fn get_field_names_at_handle(
+    plugin: &MongoPlugin,
+    engine: &EngineInterface,
+    call: &DynamicCompletionCall,
+) -> Option<Vec<DynamicSuggestion>> {
+    let coll = call
+        .call
+        .get_flag_expr("collection")
+        .and_then(|e| e.as_string())?;
+    get_field_names(handle_from_call(plugin, call)?, coll, plugin, engine)
+}
+
+fn get_field_names(
+    handle: u32,
+    coll: String,
+    plugin: &MongoPlugin,
+    engine: &EngineInterface,
+) -> Option<Vec<DynamicSuggestion>> {
+    let config = CompletionConfig::from_engine(engine);
+    let db = plugin
+        .get_completion_handle(handle, config.server_timeout)
+        .ok()?;
+    plugin
+        .completions
+        .get_or_refresh(
+            CacheKey::FieldNames(handle, coll.clone()),
+            &config,
+            move || sample_field_names(&db, &coll),
+        )
+        .map(|names| {
+            names
+                .into_iter()
+                .map(|name| DynamicSuggestion {
+                    value: name,
+                    ..Default::default()
+                })
+                .collect()
+        })
+}
+
+fn sample_field_names(db: &Database, coll: &str) -> Option<Vec<String>> {
+    let docs = db
+        .collection::<Document>(coll)
+        .aggregate([doc! {"$sample": {"size": SAMPLE_SIZE}}])
+        .run()
+        .ok()?;
+    let mut names = BTreeSet::new();
+    for doc in docs {
+        collect_field_names("", &doc.ok()?, &mut names);
+    }
+    Some(names.into_iter().collect())
+}
+
+/// Collect field names of the document, nested fields are joined by `.`.
+///
+/// Documents inside arrays are also collected, because mongodb queries them in the same way.
+fn collect_field_names(prefix: &str, doc: &Document, names: &mut BTreeSet<String>) {
+    for (k, v) in doc {
+        let name = format!("{prefix}{k}");
+        match v {
+            Bson::Document(d) => collect_field_names(&format!("{name}."), d, names),
+            Bson::Array(vals) => {
+                for v in vals {
+                    if let Bson::Document(d) = v {
+                        collect_field_names(&format!("{name}."), d, names)
+                    }
+                }
+            }
+            _ => (),
+        }
+        names.insert(name);
+    }
+}
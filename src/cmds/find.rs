@@ -1,11 +1,17 @@
-use super::get_collection_names_at_current_handle;
-use super::val_converter::{doc_to_value, value_to_doc};
+use super::concern::{collection_options, with_read_flags};
+use super::next::fetch_batch;
+use super::query_dsl::{query_filter, with_where_flag};
+use super::val_converter::{BsonOptions, doc_to_value, to_bson, value_to_doc};
 use crate::MongoPlugin;
-use mongodb::{bson::Document, options::FindOptions};
+use crate::error::mongo_error;
+use mongodb::{
+    bson::{Bson, Document, doc, oid::ObjectId},
+    options::FindOptions,
+};
 use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
 use nu_protocol::{
-    Category, DynamicSuggestion, Example, LabeledError, Record, Signature, Spanned, SyntaxShape,
-    Type, Value, engine::ArgType,
+    Category, DynamicSuggestion, Example, LabeledError, Record, Signature, Span, Spanned,
+    SyntaxShape, Type, Value, engine::ArgType, record,
 };
 
 pub struct Find;
@@ -22,7 +28,7 @@ impl SimplePluginCommand for Find {
     }
 
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("mongoc find")
+        with_where_flag(with_read_flags(Signature::build("mongoc find")))
             .optional("query object", SyntaxShape::Record(vec![]), "query object")
             .required_named(
                 "collection",
@@ -32,8 +38,8 @@ impl SimplePluginCommand for Find {
             )
             .named(
                 "db-handle",
-                SyntaxShape::Int,
-                "database handle, can get from `mongoc list`",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
                 Some('d'),
             )
             .named(
@@ -48,11 +54,31 @@ impl SimplePluginCommand for Find {
                 "sort option",
                 Some('s'),
             )
-            .input_output_type(Type::Nothing, Type::table())
+            .named(
+                "projection",
+                SyntaxShape::Record(vec![]),
+                "projection option",
+                Some('p'),
+            )
+            .named(
+                "after",
+                SyntaxShape::Record(vec![]),
+                "last row of previous page, returns rows after it in `--sort` order, `_id` is used to break ties, a 24 hex digits `_id` string is read as ObjectId unless the handle is lossless",
+                Some('a'),
+            )
+            .switch(
+                "keep-cursor",
+                "keep the cursor to fetch more documents by `mongoc next`, returns a record with `cursor` id and `documents`, cursors idle for 9 minutes are closed",
+                None,
+            )
+            .input_output_types(vec![
+                (Type::Nothing, Type::table()),
+                (Type::Nothing, Type::record()),
+            ])
             .category(Category::Database)
     }
 
-    fn examples(&self) -> Vec<Example> {
+    fn examples(&self) -> Vec<Example<'_>> {
         vec![
             Example {
                 description: "find documents in collection `students`",
@@ -69,21 +95,48 @@ impl SimplePluginCommand for Find {
                 example: "mongoc find {name: John} -d 0 -c teachers -s {\"age\": 1}",
                 result: None,
             },
+            Example {
+                description: "find `teachers` with name `John`, only returns `name` and `age` fields",
+                example: "mongoc find {name: John} -d 0 -c teachers -p {name: 1, age: 1}",
+                result: None,
+            },
+            Example {
+                description: "page through `students` ordered by age, without skipping rows",
+                example: "mut page = mongoc find -c students -s {age: 1, _id: 1} -l 1000; while ($page | is-not-empty) { $page = mongoc find -c students -s {age: 1, _id: 1} -l 1000 --after ($page | last) }",
+                result: None,
+            },
+            Example {
+                description: "page through `students` 100 rows at a time",
+                example: "let page = mongoc find -c students -l 100 --keep-cursor; mongoc next $page.cursor",
+                result: None,
+            },
+            Example {
+                description: "find adult students in Paris or Rome",
+                example: "mongoc find -c students --where {|r| $r.age > 18 and $r.city in [Paris, Rome]}",
+                result: None,
+            },
         ]
     }
     fn run(
         &self,
         plugin: &MongoPlugin,
-        _engine: &nu_plugin::EngineInterface,
+        engine: &nu_plugin::EngineInterface,
         call: &nu_plugin::EvaluatedCall,
         _input: &Value,
     ) -> Result<Value, LabeledError> {
-        let db_handle: Option<Spanned<i64>> = call.get_flag("db-handle")?;
-        let db = match db_handle {
-            None => plugin.get_handle(plugin.get_current()?, call.head)?,
-            Some(db_handle) => plugin.get_handle(db_handle.item as u8, db_handle.span)?,
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (handle_id, plugin.get_handle(handle_id, call.head)?)
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (handle_id, plugin.get_handle(handle_id, db_handle.span())?)
+            }
         };
-        let coll: String = call
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
+        let coll_name: String = call
             .get_flag("collection")?
             .expect("already check existed.");
         let limit: Spanned<i64> = call.get_flag("limit")?.unwrap_or(Spanned {
@@ -95,44 +148,304 @@ impl SimplePluginCommand for Find {
                 LabeledError::new("get invalid number").with_label("can't be negative", limit.span)
             );
         }
+        let keep_cursor = call.has_flag("keep-cursor")?;
+        if keep_cursor && limit.item == 0 {
+            return Err(LabeledError::new("get invalid number")
+                .with_label("can't be 0 when keeping cursor", limit.span));
+        }
         let limit = limit.item;
         let query: Record = call.opt(0)?.unwrap_or_default();
+        let mut query = query_filter(
+            engine,
+            call,
+            value_to_doc(query, &bson_options.filter())?,
+            &bson_options,
+        )?;
         let sort_options: Option<Record> = call.get_flag("sort")?;
-        let coll = db.collection::<Document>(&coll);
-        let mut find = coll.find(value_to_doc(query)?);
-        if let Some(sort_opt) = sort_options {
-            find = find
-                .with_options(
-                    FindOptions::builder()
-                        .sort(Some(value_to_doc(sort_opt)?))
-                        .build(),
-                )
-                .limit(limit);
+        let mut sort = sort_options
+            .map(|v| value_to_doc(v, &bson_options))
+            .transpose()?;
+        if let Some(after) = call.get_flag_value("after") {
+            // `_id` is appended, so the sort order is total and no rows are skipped.
+            let sort = sort.get_or_insert_with(Document::new);
+            if !sort.contains_key("_id") {
+                sort.insert("_id", 1);
+            }
+            let span = after.span();
+            let predicate = keyset_filter(sort, after.into_record()?, span, &bson_options)?;
+            query = if query.is_empty() {
+                predicate
+            } else {
+                doc! { "$and": [query, predicate] }
+            };
+        }
+        let projection: Option<Record> = call.get_flag("projection")?;
+        let coll = db.collection_with_options::<Document>(&coll_name, collection_options(call)?);
+        let mut find = coll.find(query);
+        if let Some(sort) = sort {
+            find = find.with_options(FindOptions::builder().sort(Some(sort)).build());
+        }
+        // limit is the batch size of kept cursor, so all documents can be fetched.
+        if keep_cursor {
+            find = find.batch_size(u32::try_from(limit).unwrap_or(u32::MAX));
         } else {
             find = find.limit(limit)
         }
-        let result = find.run().map_err(|e| LabeledError::new(format!("{e}")))?;
+        if let Some(projection) = projection {
+            find = find.projection(value_to_doc(projection, &bson_options)?);
+        }
+        let mut result = find.run().map_err(|e| mongo_error(e, call))?;
+        if keep_cursor {
+            let (rows, exhausted) = fetch_batch(&mut result, limit as u64, call, &bson_options)?;
+            let cursor = if exhausted {
+                Value::nothing(call.head)
+            } else {
+                let fetched = rows.len() as u64;
+                let id =
+                    plugin
+                        .cursors
+                        .insert(result, handle_id, coll_name, limit as u64, fetched)?;
+                Value::int(id.into(), call.head)
+            };
+            return Ok(Value::record(
+                record! {
+                    "cursor" => cursor,
+                    "documents" => Value::list(rows, call.head),
+                },
+                call.head,
+            ));
+        }
 
         let mut rows = vec![];
         for doc in result {
-            let doc = doc.map_err(|e| LabeledError::new(format!("{e}")))?;
-            rows.push(doc_to_value(doc, call.head))
+            let doc = doc.map_err(|e| mongo_error(e, call))?;
+            rows.push(doc_to_value(doc, call.head, &bson_options))
         }
         Ok(Value::list(rows, call.head))
     }
+    #[allow(deprecated)]
     fn get_dynamic_completion(
         &self,
         plugin: &Self::Plugin,
-        _engine: &EngineInterface,
-        _call: DynamicCompletionCall,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
         arg_type: ArgType,
         _experimental: nu_protocol::engine::ExperimentalMarker,
     ) -> Option<Vec<DynamicSuggestion>> {
         match arg_type {
             ArgType::Flag(name) if name == "collection" => {
-                super::get_collection_names_at_current_handle(plugin)
+                super::get_collection_names_at_handle(plugin, engine, &call)
+            }
+            ArgType::Flag(name) if name == "sort" || name == "projection" => {
+                super::get_field_names_at_handle(plugin, engine, &call)
             }
+            ArgType::Positional(0) => super::get_field_names_at_handle(plugin, engine, &call),
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
             _ => None,
         }
     }
 }
+
+/// Build the predicate which matches rows after `after` in `sort` order, e.g: sort by
+/// `{a: 1, _id: 1}` gives `{$or: [{a: {$gt: x}}, {a: x, _id: {$gt: y}}]}`.
+///
+/// Missing values are sorted as null, before all other values, but `$gt` and `$lt` don't compare
+/// null with other types, so they're handled by `$ne: null` and `null` instead.
+fn keyset_filter(
+    sort: &Document,
+    after: Record,
+    span: Span,
+    bson_options: &BsonOptions,
+) -> Result<Document, LabeledError> {
+    let mut equals = Document::new();
+    let mut branches = vec![];
+    for (key, direction) in sort {
+        let op = match direction {
+            Bson::Int32(1) | Bson::Int64(1) => "$gt",
+            Bson::Int32(-1) | Bson::Int64(-1) => "$lt",
+            other => {
+                return Err(
+                    LabeledError::new("unsupported sort for `--after`").with_label(
+                        format!("sort direction of `{key}` is {other}, should be 1 or -1"),
+                        span,
+                    ),
+                );
+            }
+        };
+        let value = match key_value(&after, key) {
+            // missing values are sorted as null.
+            None => Bson::Null,
+            Some(value) => match (key.as_str(), to_bson(value, bson_options, key)?) {
+                // ObjectId is returned as string by `mongoc find` unless the handle is lossless.
+                ("_id", Bson::String(s)) if !bson_options.lossless => {
+                    match ObjectId::parse_str(&s) {
+                        Ok(oid) => Bson::ObjectId(oid),
+                        Err(_) => Bson::String(s),
+                    }
+                }
+                (_, v) => v,
+            },
+        };
+        let mut branch = equals.clone();
+        match (op, &value) {
+            ("$gt", Bson::Null) => {
+                branch.insert(key, doc! { "$ne": null });
+            }
+            // nothing is sorted after null in descending order.
+            ("$lt", Bson::Null) => {
+                equals.insert(key, value);
+                continue;
+            }
+            // null is sorted after other values in descending order.
+            ("$lt", _) => {
+                branch.insert(
+                    "$or",
+                    vec![doc! { key: { op: value.clone() } }, doc! { key: null }],
+                );
+            }
+            _ => {
+                branch.insert(key, doc! { op: value.clone() });
+            }
+        }
+        branches.push(Bson::Document(branch));
+        equals.insert(key, value);
+    }
+    Ok(doc! { "$or": branches })
+}
+
+/// Get value of a dotted key from the row, e.g: `address.city`.
+fn key_value(row: &Record, key: &str) -> Option<Value> {
+    let mut parts = key.split('.');
+    let mut value = row.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_record().ok()?.get(part)?;
+    }
+    Some(value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OID: &str = "65a1b2c3d4e5f60718293a4b";
+
+    fn filter(sort: Document, after: Record) -> Document {
+        keyset_filter(&sort, after, Span::test_data(), &BsonOptions::default()).unwrap()
+    }
+
+    fn int(i: i64) -> Value {
+        Value::test_int(i)
+    }
+
+    #[test]
+    fn ascending_keys() {
+        assert_eq!(
+            filter(
+                doc! { "a": 1, "_id": 1 },
+                record! { "a" => int(5), "_id" => int(7) }
+            ),
+            doc! { "$or": [{ "a": { "$gt": 5 } }, { "a": 5, "_id": { "$gt": 7 } }] }
+        );
+    }
+
+    #[test]
+    fn descending_keys_include_null() {
+        assert_eq!(
+            filter(
+                doc! { "a": -1, "_id": 1 },
+                record! { "a" => int(5), "_id" => int(7) }
+            ),
+            doc! { "$or": [
+                { "$or": [{ "a": { "$lt": 5 } }, { "a": null }] },
+                { "a": 5, "_id": { "$gt": 7 } },
+            ] }
+        );
+    }
+
+    #[test]
+    fn dotted_keys() {
+        let after = record! {
+            "address" => Value::test_record(record! { "city" => Value::test_string("x") }),
+            "_id" => int(7),
+        };
+        assert_eq!(
+            filter(doc! { "address.city": 1, "_id": 1 }, after),
+            doc! { "$or": [
+                { "address.city": { "$gt": "x" } },
+                { "address.city": "x", "_id": { "$gt": 7 } },
+            ] }
+        );
+    }
+
+    #[test]
+    fn null_and_missing_values() {
+        let expected = doc! { "$or": [
+            { "a": { "$ne": null } },
+            { "a": null, "_id": { "$gt": 7 } },
+        ] };
+        assert_eq!(
+            filter(
+                doc! { "a": 1, "_id": 1 },
+                record! { "a" => Value::test_nothing(), "_id" => int(7) }
+            ),
+            expected
+        );
+        assert_eq!(
+            filter(doc! { "a": 1, "_id": 1 }, record! { "_id" => int(7) }),
+            expected
+        );
+        // nothing is after null in descending order, except ties.
+        assert_eq!(
+            filter(doc! { "a": -1, "_id": 1 }, record! { "_id" => int(7) }),
+            doc! { "$or": [{ "a": null, "_id": { "$gt": 7 } }] }
+        );
+    }
+
+    #[test]
+    fn id_strings_are_object_ids_unless_lossless() {
+        let oid = ObjectId::parse_str(OID).unwrap();
+        assert_eq!(
+            filter(
+                doc! { "_id": 1 },
+                record! { "_id" => Value::test_string(OID) }
+            ),
+            doc! { "$or": [{ "_id": { "$gt": oid } }] }
+        );
+        assert_eq!(
+            filter(
+                doc! { "_id": 1 },
+                record! { "_id" => Value::test_string("abc") }
+            ),
+            doc! { "$or": [{ "_id": { "$gt": "abc" } }] }
+        );
+
+        let lossless = BsonOptions {
+            lossless: true,
+            ..Default::default()
+        };
+        let after = record! { "_id" => Value::test_string(OID) };
+        assert_eq!(
+            keyset_filter(&doc! { "_id": 1 }, after, Span::test_data(), &lossless).unwrap(),
+            doc! { "$or": [{ "_id": { "$gt": OID } }] }
+        );
+        let after = record! {
+            "_id" => Value::test_record(record! { "$oid" => Value::test_string(OID) }),
+        };
+        assert_eq!(
+            keyset_filter(&doc! { "_id": 1 }, after, Span::test_data(), &lossless).unwrap(),
+            doc! { "$or": [{ "_id": { "$gt": oid } }] }
+        );
+    }
+
+    #[test]
+    fn invalid_sort_direction() {
+        let err = keyset_filter(
+            &doc! { "a": "asc" },
+            record! { "a" => int(1) },
+            Span::test_data(),
+            &BsonOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.msg, "unsupported sort for `--after`");
+    }
+}
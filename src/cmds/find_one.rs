@@ -1,11 +1,13 @@
-use super::val_converter::{doc_to_value, value_to_doc};
+use super::concern::{collection_options, with_read_flags};
+use super::val_converter::{BsonOptions, doc_to_value, value_to_doc};
 use crate::MongoPlugin;
+use crate::error::mongo_error;
 use mongodb::bson::Document;
 use mongodb::options::FindOneOptions;
 use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
 use nu_protocol::{
-    Category, DynamicSuggestion, Example, LabeledError, Record, Signature, Spanned, SyntaxShape,
-    Type, Value, engine::ArgType,
+    Category, DynamicSuggestion, Example, LabeledError, Record, Signature, SyntaxShape, Type,
+    Value, engine::ArgType,
 };
 
 pub struct FindOne;
@@ -22,7 +24,7 @@ impl SimplePluginCommand for FindOne {
     }
 
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("mongoc find-one")
+        with_read_flags(Signature::build("mongoc find-one"))
             .optional("query object", SyntaxShape::Record(vec![]), "query object")
             .required_named(
                 "collection",
@@ -32,8 +34,8 @@ impl SimplePluginCommand for FindOne {
             )
             .named(
                 "db-handle",
-                SyntaxShape::Int,
-                "database handle, can get from `mongoc list`",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
                 Some('d'),
             )
             .named(
@@ -42,11 +44,17 @@ impl SimplePluginCommand for FindOne {
                 "sort option",
                 Some('s'),
             )
+            .named(
+                "projection",
+                SyntaxShape::Record(vec![]),
+                "projection option",
+                Some('p'),
+            )
             .input_output_type(Type::Nothing, Type::record())
             .category(Category::Database)
     }
 
-    fn examples(&self) -> Vec<Example> {
+    fn examples(&self) -> Vec<Example<'_>> {
         vec![
             Example {
                 description: "find a student with age `19`, in a `students` collection",
@@ -69,50 +77,65 @@ impl SimplePluginCommand for FindOne {
     fn run(
         &self,
         plugin: &MongoPlugin,
-        _engine: &nu_plugin::EngineInterface,
+        engine: &nu_plugin::EngineInterface,
         call: &nu_plugin::EvaluatedCall,
         _input: &Value,
     ) -> Result<Value, LabeledError> {
-        let db_handle: Option<Spanned<i64>> = call.get_flag("db-handle")?;
-        let db = match db_handle {
-            None => plugin.get_handle(plugin.get_current()?, call.head)?,
-            Some(db_handle) => plugin.get_handle(db_handle.item as u8, db_handle.span)?,
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (handle_id, plugin.get_handle(handle_id, call.head)?)
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (handle_id, plugin.get_handle(handle_id, db_handle.span())?)
+            }
         };
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
         let coll: String = call
             .get_flag("collection")?
             .expect("already check existed.");
         let query: Record = call.opt(0)?.unwrap_or_default();
         let sort_options: Option<Record> = call.get_flag("sort")?;
-        let coll = db.collection::<Document>(&coll);
-        let mut find_one = coll.find_one(value_to_doc(query)?);
+        let projection: Option<Record> = call.get_flag("projection")?;
+        let coll = db.collection_with_options::<Document>(&coll, collection_options(call)?);
+        let mut find_one = coll.find_one(value_to_doc(query, &bson_options.filter())?);
         if let Some(sort_opt) = sort_options {
             find_one = find_one.with_options(
                 FindOneOptions::builder()
-                    .sort(Some(value_to_doc(sort_opt)?))
+                    .sort(Some(value_to_doc(sort_opt, &bson_options)?))
                     .build(),
             )
         }
-        let result = find_one
-            .run()
-            .map_err(|e| LabeledError::new(format!("{e}")))?;
+        if let Some(projection) = projection {
+            find_one = find_one.projection(value_to_doc(projection, &bson_options)?);
+        }
+        let result = find_one.run().map_err(|e| mongo_error(e, call))?;
 
         match result {
             None => Ok(Value::nothing(call.head)),
-            Some(d) => Ok(doc_to_value(d, call.head)),
+            Some(d) => Ok(doc_to_value(d, call.head, &bson_options)),
         }
     }
+    #[allow(deprecated)]
     fn get_dynamic_completion(
         &self,
         plugin: &Self::Plugin,
-        _engine: &EngineInterface,
-        _call: DynamicCompletionCall,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
         arg_type: ArgType,
         _experimental: nu_protocol::engine::ExperimentalMarker,
     ) -> Option<Vec<DynamicSuggestion>> {
         match arg_type {
             ArgType::Flag(name) if name == "collection" => {
-                super::get_collection_names_at_current_handle(plugin)
+                super::get_collection_names_at_handle(plugin, engine, &call)
+            }
+            ArgType::Flag(name) if name == "sort" || name == "projection" => {
+                super::get_field_names_at_handle(plugin, engine, &call)
             }
+            ArgType::Positional(0) => super::get_field_names_at_handle(plugin, engine, &call),
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
             _ => None,
         }
     }
@@ -0,0 +1,69 @@
+use crate::MongoPlugin;
+use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Signature, SyntaxShape, Type, Value,
+    engine::ArgType,
+};
+
+pub struct Forget;
+
+impl SimplePluginCommand for Forget {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc forget"
+    }
+
+    fn description(&self) -> &str {
+        "stop restoring a persisted mongodb handle after plugin restarts"
+    }
+
+    fn extra_description(&self) -> &str {
+        "The handle is removed from session file, but it's still available until `mongoc remove` or plugin restarts."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc forget")
+            .required(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name to forget, can get from `mongoc list`",
+            )
+            .input_output_type(Type::Nothing, Type::Nothing)
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![Example {
+            description: "Forget handle `prod`",
+            example: "mongoc forget prod",
+            result: None,
+        }]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let handle_id: Value = call.req(0)?;
+        plugin.forget_handle(plugin.resolve_handle_id(&handle_id)?, handle_id.span())?;
+        Ok(Value::nothing(call.head))
+    }
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        _engine: &EngineInterface,
+        _call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Positional(0) => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
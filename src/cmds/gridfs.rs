@@ -0,0 +1,95 @@
+use super::val_converter::{BsonOptions, bson_to_value};
+use crate::MongoPlugin;
+use crate::error::mongo_error_at;
+use mongodb::bson::{Bson, doc, oid::ObjectId};
+use mongodb::options::GridFsBucketOptions;
+use mongodb::sync::{Database, gridfs::GridFsBucket};
+use nu_plugin::{EngineInterface, EvaluatedCall, PluginCommand};
+use nu_protocol::{Category, LabeledError, PipelineData, Signature, Span, Type, Value};
+
+pub(super) const DEFAULT_BUCKET: &str = "fs";
+
+#[derive(Clone)]
+pub struct GridFsCmd;
+
+impl PluginCommand for GridFsCmd {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc gridfs"
+    }
+
+    fn description(&self) -> &str {
+        "Operate with files stored in mongodb GridFS"
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc gridfs")
+            .category(Category::Database)
+            .input_output_types(vec![(Type::Nothing, Type::String)])
+    }
+
+    fn extra_description(&self) -> &str {
+        "You must use one of the following subcommands. Using this command as-is will only produce this help message."
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        engine: &EngineInterface,
+        call: &EvaluatedCall,
+        _input: PipelineData,
+    ) -> Result<PipelineData, LabeledError> {
+        Ok(PipelineData::Value(
+            Value::string(engine.get_help()?, call.head),
+            None,
+        ))
+    }
+}
+
+/// Get GridFS bucket from the given database, bucket name defaults to `fs`.
+pub(super) fn get_bucket(db: &Database, bucket: Option<String>) -> GridFsBucket {
+    db.gridfs_bucket(
+        GridFsBucketOptions::builder()
+            .bucket_name(bucket.unwrap_or_else(|| DEFAULT_BUCKET.to_string()))
+            .build(),
+    )
+}
+
+/// Render a file id, ObjectId is rendered as string so it can be passed to `mongoc gridfs get`.
+pub(super) fn file_id_value(id: Bson, span: Span, options: &BsonOptions) -> Value {
+    match id {
+        Bson::ObjectId(oid) => Value::string(oid.to_string(), span),
+        other => bson_to_value(other, span, options),
+    }
+}
+
+/// Resolve file ids by the given target.
+///
+/// The target is treated as an ObjectId if it can be parsed, otherwise it's a file name,
+/// in which case all revisions of the file are returned.
+pub(super) fn resolve_file_ids(
+    bucket: &GridFsBucket,
+    target: &str,
+    span: Span,
+) -> Result<Vec<Bson>, LabeledError> {
+    let filter = match ObjectId::parse_str(target) {
+        Ok(oid) => doc! {"_id": oid},
+        Err(_) => doc! {"filename": target},
+    };
+    let files = bucket
+        .find(filter)
+        .run()
+        .map_err(|e| mongo_error_at(e, span))?;
+    let mut ids = vec![];
+    for file in files {
+        let file = file.map_err(|e| mongo_error_at(e, span))?;
+        ids.push(file.id)
+    }
+    if ids.is_empty() {
+        return Err(LabeledError::new("GridFS file doesn't exist")
+            .with_label("no file matches the given id or filename", span)
+            .with_help("You can run `mongoc gridfs list` to list all available files"));
+    }
+    Ok(ids)
+}
@@ -0,0 +1,137 @@
+use super::gridfs::get_bucket;
+use crate::MongoPlugin;
+use crate::error::mongo_error_at;
+use mongodb::bson::{Bson, oid::ObjectId};
+use nu_plugin::{DynamicCompletionCall, EngineInterface, EvaluatedCall, PluginCommand};
+use nu_protocol::{
+    ByteStream, ByteStreamType, Category, DynamicSuggestion, Example, LabeledError, PipelineData,
+    Signature, Spanned, SyntaxShape, Type, Value, engine::ArgType,
+};
+
+pub struct GridFsGet;
+
+impl PluginCommand for GridFsGet {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc gridfs get"
+    }
+
+    fn description(&self) -> &str {
+        "download a file from GridFS bucket"
+    }
+
+    fn extra_description(&self) -> &str {
+        "The file can be specified by ObjectId or by file name, the latest revision is returned when specified by file name."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc gridfs get")
+            .required(
+                "file",
+                SyntaxShape::String,
+                "file id or file name to download",
+            )
+            .named(
+                "bucket",
+                SyntaxShape::String,
+                "GridFS bucket name, default is `fs`",
+                Some('b'),
+            )
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .named(
+                "revision",
+                SyntaxShape::Int,
+                "file revision to download, 0 is the original file, -1 is the most recent one, it can't be used with a file id",
+                Some('r'),
+            )
+            .input_output_type(Type::Nothing, Type::Binary)
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "download file by id, and save it to local",
+                example: "mongoc gridfs get 66b0a5ff9bd5b6f0e8a0e1a2 | save report.pdf",
+                result: None,
+            },
+            Example {
+                description: "download the original revision of `report.pdf` in `docs` bucket",
+                example: "mongoc gridfs get report.pdf -b docs -r 0",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &EngineInterface,
+        call: &EvaluatedCall,
+        _input: PipelineData,
+    ) -> Result<PipelineData, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let db = match db_handle {
+            None => plugin.get_handle(plugin.get_current()?, call.head)?,
+            Some(db_handle) => {
+                plugin.get_handle(plugin.resolve_handle_id(&db_handle)?, db_handle.span())?
+            }
+        };
+        let file: Spanned<String> = call.req(0)?;
+        let revision: Option<Spanned<i64>> = call.get_flag("revision")?;
+        let bucket = get_bucket(&db, call.get_flag("bucket")?);
+        let stream = match (ObjectId::parse_str(&file.item), revision) {
+            (Ok(_), Some(revision)) => {
+                return Err(LabeledError::new("revision of a file id")
+                    .with_label("`--revision` only applies to file names", revision.span)
+                    .with_help(
+                        "A file id refers to one revision already, please remove `--revision`",
+                    ));
+            }
+            (Ok(oid), None) => bucket.open_download_stream(Bson::ObjectId(oid)).run(),
+            (Err(_), revision) => {
+                let mut download = bucket.open_download_stream_by_name(&file.item);
+                if let Some(revision) = revision {
+                    let revision = i32::try_from(revision.item).map_err(|_| {
+                        LabeledError::new("revision out of range")
+                            .with_label("should fit in a 32-bit integer", revision.span)
+                    })?;
+                    download = download.revision(revision);
+                }
+                download.run()
+            }
+        }
+        .map_err(|e| mongo_error_at(e, file.span))?;
+
+        Ok(PipelineData::ByteStream(
+            ByteStream::read(
+                stream,
+                call.head,
+                engine.signals().clone(),
+                ByteStreamType::Binary,
+            ),
+            None,
+        ))
+    }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        _engine: &EngineInterface,
+        _call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use super::gridfs::{file_id_value, get_bucket};
+use super::val_converter::{BsonOptions, doc_to_value, value_to_doc};
+use crate::MongoPlugin;
+use crate::error::mongo_error;
+use mongodb::bson::doc;
+use mongodb::options::GridFsFindOptions;
+use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Record, Signature, Spanned, SyntaxShape,
+    Type, Value, engine::ArgType, record,
+};
+
+pub struct GridFsList;
+
+impl SimplePluginCommand for GridFsList {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc gridfs list"
+    }
+
+    fn description(&self) -> &str {
+        "list files stored in GridFS bucket"
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc gridfs list")
+            .optional(
+                "query object",
+                SyntaxShape::Record(vec![]),
+                "query object against files collection",
+            )
+            .named(
+                "bucket",
+                SyntaxShape::String,
+                "GridFS bucket name, default is `fs`",
+                Some('b'),
+            )
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .named(
+                "limit",
+                SyntaxShape::Int,
+                "limit files to return",
+                Some('l'),
+            )
+            .input_output_type(Type::Nothing, Type::table())
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "list all files in default bucket",
+                example: "mongoc gridfs list",
+                result: None,
+            },
+            Example {
+                description: "list files named `report.pdf` in `docs` bucket",
+                example: "mongoc gridfs list {filename: report.pdf} -b docs",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (handle_id, plugin.get_handle(handle_id, call.head)?)
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (handle_id, plugin.get_handle(handle_id, db_handle.span())?)
+            }
+        };
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
+        let limit: Option<Spanned<i64>> = call.get_flag("limit")?;
+        if let Some(limit) = &limit
+            && limit.item.is_negative()
+        {
+            return Err(
+                LabeledError::new("get invalid number").with_label("can't be negative", limit.span)
+            );
+        }
+        let query: Record = call.opt(0)?.unwrap_or_default();
+        let bucket = get_bucket(&db, call.get_flag("bucket")?);
+        let files = bucket
+            .find(value_to_doc(query, &bson_options.filter())?)
+            .with_options(
+                GridFsFindOptions::builder()
+                    .limit(limit.map(|l| l.item))
+                    .sort(doc! {"uploadDate": -1})
+                    .build(),
+            )
+            .run()
+            .map_err(|e| mongo_error(e, call))?;
+
+        let mut rows = vec![];
+        for file in files {
+            let file = file.map_err(|e| mongo_error(e, call))?;
+            rows.push(Value::record(
+                record! {
+                    "id" => file_id_value(file.id, call.head, &bson_options),
+                    "filename" => match file.filename {
+                        None => Value::nothing(call.head),
+                        Some(name) => Value::string(name, call.head),
+                    },
+                    "length" => Value::filesize(file.length as i64, call.head),
+                    "chunk_size" => Value::filesize(i64::from(file.chunk_size_bytes), call.head),
+                    "upload_date" => Value::date(file.upload_date.to_chrono().into(), call.head),
+                    "metadata" => match file.metadata {
+                        None => Value::nothing(call.head),
+                        Some(metadata) => doc_to_value(metadata, call.head, &bson_options),
+                    },
+                },
+                call.head,
+            ))
+        }
+        Ok(Value::list(rows, call.head))
+    }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        _engine: &EngineInterface,
+        _call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
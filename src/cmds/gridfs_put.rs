@@ -0,0 +1,159 @@
+use super::gridfs::{file_id_value, get_bucket};
+use super::val_converter::{BsonOptions, value_to_doc};
+use crate::MongoPlugin;
+use crate::error::mongo_error;
+use nu_plugin::{DynamicCompletionCall, EngineInterface, EvaluatedCall, PluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, PipelineData, Record, Signature, Spanned,
+    SyntaxShape, Type, Value, engine::ArgType,
+};
+use std::io::Write;
+
+pub struct GridFsPut;
+
+impl PluginCommand for GridFsPut {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc gridfs put"
+    }
+
+    fn description(&self) -> &str {
+        "upload piped bytes to GridFS bucket, returns the id of uploaded file"
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc gridfs put")
+            .required("filename", SyntaxShape::String, "file name to store")
+            .named(
+                "bucket",
+                SyntaxShape::String,
+                "GridFS bucket name, default is `fs`",
+                Some('b'),
+            )
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .named(
+                "metadata",
+                SyntaxShape::Record(vec![]),
+                "user metadata stored along with the file",
+                Some('m'),
+            )
+            .named(
+                "chunk-size",
+                SyntaxShape::Filesize,
+                "chunk size of the file, default is 255 KiB",
+                None,
+            )
+            .input_output_types(vec![
+                (Type::Binary, Type::String),
+                (Type::String, Type::String),
+            ])
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "upload a local file to default bucket",
+                example: "open --raw report.pdf | mongoc gridfs put report.pdf",
+                result: None,
+            },
+            Example {
+                description: "upload a local file with metadata and 1MiB chunk size to `docs` bucket",
+                example: "open --raw report.pdf | mongoc gridfs put report.pdf -b docs -m {owner: John} --chunk-size 1MiB",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &EngineInterface,
+        call: &EvaluatedCall,
+        input: PipelineData,
+    ) -> Result<PipelineData, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (handle_id, plugin.get_handle(handle_id, call.head)?)
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (handle_id, plugin.get_handle(handle_id, db_handle.span())?)
+            }
+        };
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
+        let filename: String = call.req(0)?;
+        let metadata: Option<Record> = call.get_flag("metadata")?;
+        let chunk_size: Option<Spanned<i64>> = call.get_flag("chunk-size")?;
+        let bucket = get_bucket(&db, call.get_flag("bucket")?);
+
+        let mut upload = bucket.open_upload_stream(&filename);
+        if let Some(metadata) = metadata {
+            upload = upload.metadata(value_to_doc(metadata, &bson_options)?);
+        }
+        if let Some(chunk_size) = chunk_size {
+            let size = u32::try_from(chunk_size.item)
+                .ok()
+                .filter(|s| *s > 0)
+                .ok_or_else(|| {
+                    LabeledError::new("invalid chunk size")
+                        .with_label("should be positive and less than 4GiB", chunk_size.span)
+                })?;
+            upload = upload.chunk_size_bytes(size);
+        }
+        let mut upload = upload.run().map_err(|e| mongo_error(e, call))?;
+
+        let written = match input {
+            PipelineData::ByteStream(stream, ..) => match stream.reader() {
+                Some(mut reader) => std::io::copy(&mut reader, &mut upload).map(|_| ()),
+                None => Ok(()),
+            },
+            PipelineData::Value(Value::Binary { val, .. }, ..) => upload.write_all(&val),
+            PipelineData::Value(Value::String { val, .. }, ..) => upload.write_all(val.as_bytes()),
+            other => {
+                let _ = upload.abort();
+                return Err(LabeledError::new("invalid input").with_label(
+                    format!("expected binary or string input, got {}", other.get_type()),
+                    call.head,
+                ));
+            }
+        };
+        if let Err(e) = written {
+            let _ = upload.abort();
+            return Err(LabeledError::new(format!("{e}")).with_label("upload failed", call.head));
+        }
+        upload.close().map_err(|e| {
+            LabeledError::new(format!("{e}")).with_label("upload failed", call.head)
+        })?;
+        // files and chunks collections may be created by the upload.
+        plugin.invalidate_completions(handle_id, None);
+
+        Ok(PipelineData::Value(
+            file_id_value(upload.id().clone(), call.head, &bson_options),
+            None,
+        ))
+    }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        _engine: &EngineInterface,
+        _call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use super::gridfs::{get_bucket, resolve_file_ids};
+use crate::MongoPlugin;
+use crate::error::mongo_error;
+use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Signature, Spanned, SyntaxShape, Type,
+    Value, engine::ArgType,
+};
+
+pub struct GridFsRename;
+
+impl SimplePluginCommand for GridFsRename {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc gridfs rename"
+    }
+
+    fn description(&self) -> &str {
+        "rename a file in GridFS bucket"
+    }
+
+    fn extra_description(&self) -> &str {
+        "The file can be specified by ObjectId or by file name, all revisions are renamed when specified by file name."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc gridfs rename")
+            .required(
+                "file",
+                SyntaxShape::String,
+                "file id or file name to rename",
+            )
+            .required("new-name", SyntaxShape::String, "new file name")
+            .named(
+                "bucket",
+                SyntaxShape::String,
+                "GridFS bucket name, default is `fs`",
+                Some('b'),
+            )
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .input_output_type(Type::Nothing, Type::Nothing)
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "rename file by id",
+                example: "mongoc gridfs rename 66b0a5ff9bd5b6f0e8a0e1a2 report-2024.pdf",
+                result: None,
+            },
+            Example {
+                description: "rename `report.pdf` to `report-2024.pdf` in `docs` bucket",
+                example: "mongoc gridfs rename report.pdf report-2024.pdf -b docs",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let db = match db_handle {
+            None => plugin.get_handle(plugin.get_current()?, call.head)?,
+            Some(db_handle) => {
+                plugin.get_handle(plugin.resolve_handle_id(&db_handle)?, db_handle.span())?
+            }
+        };
+        let file: Spanned<String> = call.req(0)?;
+        let new_name: String = call.req(1)?;
+        let bucket = get_bucket(&db, call.get_flag("bucket")?);
+        for id in resolve_file_ids(&bucket, &file.item, file.span)? {
+            bucket
+                .rename(id, &new_name)
+                .run()
+                .map_err(|e| mongo_error(e, call))?;
+        }
+        Ok(Value::nothing(call.head))
+    }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        _engine: &EngineInterface,
+        _call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::MongoPlugin;
+use nu_plugin::DynamicCompletionCall;
+use nu_protocol::{DynamicSuggestion, Span, ast::Expr};
+
+/// Get all handle ids and names, with host and database name as description.
+pub fn get_handle_ids(plugin: &MongoPlugin) -> Option<Vec<DynamicSuggestion>> {
+    let mut handles = plugin.list_handles();
+    handles.sort_by_key(|k| k.0);
+    let mut suggestions = vec![];
+    for (id, name, conn_str) in handles {
+        let db_name = plugin
+            .get_handle(id, Span::unknown())
+            .map(|db| db.name().to_string())
+            .unwrap_or_default();
+        let description = format!("{}/{db_name}", host_of(&conn_str));
+        suggestions.push(DynamicSuggestion {
+            value: id.to_string(),
+            description: Some(description.clone()),
+            ..Default::default()
+        });
+        if let Some(name) = name {
+            suggestions.push(DynamicSuggestion {
+                value: name,
+                description: Some(description),
+                ..Default::default()
+            });
+        }
+    }
+    Some(suggestions)
+}
+
+/// Get database handle from `db-handle` flag of the call, fallback to current handle if the flag
+/// is not given.
+///
+/// Returns None if the flag is not a literal, e.g: a variable, because it's unknown before the
+/// command runs.
+pub fn handle_from_call(plugin: &MongoPlugin, call: &DynamicCompletionCall) -> Option<u32> {
+    match call.call.get_flag_expr("db-handle").map(|e| &e.expr) {
+        None => plugin.get_current().ok(),
+        Some(Expr::Int(id)) => u32::try_from(*id).ok(),
+        Some(Expr::String(name)) => plugin.get_alias(name),
+        Some(_) => None,
+    }
+}
+
+/// Get hosts part of the connection string, credentials are stripped.
+fn host_of(conn_str: &str) -> &str {
+    let rest = conn_str
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(conn_str);
+    let rest = rest.split(['/', '?']).next().unwrap_or(rest);
+    rest.rsplit_once('@')
+        .map(|(_, hosts)| hosts)
+        .unwrap_or(rest)
+}
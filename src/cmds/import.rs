@@ -0,0 +1,988 @@
+use super::bulk_write::{
+    MAX_BATCH_BYTES, MAX_BATCH_OPS, count_of, indexed_docs, statement_size, write_concern_error,
+    write_errors,
+};
+use super::concern::{collection_options, with_write_flags};
+use super::val_converter::BsonOptions;
+use crate::MongoPlugin;
+use crate::error::{mongo_error, mongo_error_at};
+use mongodb::bson::{Bson, Document, doc, oid::ObjectId, to_bson};
+use mongodb::error::{Error, ErrorKind};
+use mongodb::sync::{Collection, Database};
+use nu_plugin::{DynamicCompletionCall, EngineInterface, EvaluatedCall, PluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, PipelineData, Record, Signature, Span,
+    Spanned, SyntaxShape, Type, Value, engine::ArgType, record,
+};
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+use std::path::Path;
+
+pub struct Import;
+
+#[derive(Clone, Copy)]
+enum ImportFormat {
+    /// A json array of extended json documents, or concatenated extended json documents.
+    Ejson,
+    /// One extended json document per line.
+    Ndjson,
+    /// Concatenated raw bson documents, compatible with mongodump.
+    Bson,
+    /// Csv with a header line.
+    Csv,
+}
+
+impl ImportFormat {
+    fn parse(format: &Spanned<String>) -> Result<Self, LabeledError> {
+        match format.item.as_str() {
+            "ejson" | "json" => Ok(Self::Ejson),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            "bson" => Ok(Self::Bson),
+            "csv" => Ok(Self::Csv),
+            _ => Err(LabeledError::new("invalid import format")
+                .with_label("unknown format", format.span)
+                .with_help("supported formats are `ejson`, `ndjson`, `bson` and `csv`")),
+        }
+    }
+
+    /// Guess import format by file extension, fallback to ndjson.
+    fn from_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::Ejson,
+            Some("bson") => Self::Bson,
+            Some("csv") => Self::Csv,
+            _ => Self::Ndjson,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ImportMode {
+    /// Insert documents, duplicated documents are reported as failures.
+    Insert,
+    /// Replace documents which match the upsert fields, insert if not existed.
+    Upsert,
+    /// Merge fields into documents which match the upsert fields, insert if not existed.
+    Merge,
+}
+
+impl ImportMode {
+    fn parse(mode: &Spanned<String>) -> Result<Self, LabeledError> {
+        match mode.item.as_str() {
+            "insert" => Ok(Self::Insert),
+            "upsert" => Ok(Self::Upsert),
+            "merge" => Ok(Self::Merge),
+            _ => Err(LabeledError::new("invalid import mode")
+                .with_label("unknown mode", mode.span)
+                .with_help("supported modes are `insert`, `upsert` and `merge`")),
+        }
+    }
+}
+
+/// Type of a csv column.
+#[derive(Clone, Copy)]
+enum ColumnType {
+    Auto,
+    String,
+    Int32,
+    Int64,
+    Double,
+    Bool,
+    Date,
+    ObjectId,
+}
+
+impl ColumnType {
+    fn parse(name: &str, span: Span) -> Result<Self, LabeledError> {
+        match name {
+            "auto" => Ok(Self::Auto),
+            "string" => Ok(Self::String),
+            "int32" => Ok(Self::Int32),
+            "int" | "int64" => Ok(Self::Int64),
+            "double" | "float" => Ok(Self::Double),
+            "bool" => Ok(Self::Bool),
+            "date" => Ok(Self::Date),
+            "oid" | "objectid" => Ok(Self::ObjectId),
+            _ => Err(LabeledError::new("invalid column type")
+                .with_label("unknown column type", span)
+                .with_help("supported column types are `auto`, `string`, `int32`, `int64`, `double`, `bool`, `date` and `oid`")),
+        }
+    }
+
+    /// Convert a csv field, integers of `auto` columns follow `int_fields` and `integer_mode` like
+    /// other commands.
+    fn convert(
+        self,
+        field: &str,
+        header: &str,
+        bson_options: &BsonOptions,
+    ) -> Result<Bson, String> {
+        let converted = match self {
+            Self::Auto => {
+                if let Ok(i) = field.parse::<i64>() {
+                    bson_options.int_at(header, i)
+                } else if let Ok(f) = field.parse::<f64>() {
+                    Bson::Double(f)
+                } else if let Ok(b) = field.parse::<bool>() {
+                    Bson::Boolean(b)
+                } else {
+                    Bson::String(field.to_string())
+                }
+            }
+            Self::String => Bson::String(field.to_string()),
+            Self::Int32 => Bson::Int32(field.parse().map_err(|e| format!("{e}"))?),
+            Self::Int64 => Bson::Int64(field.parse().map_err(|e| format!("{e}"))?),
+            Self::Double => Bson::Double(field.parse().map_err(|e| format!("{e}"))?),
+            Self::Bool => Bson::Boolean(field.parse().map_err(|e| format!("{e}"))?),
+            Self::Date => {
+                let dt = chrono::DateTime::parse_from_rfc3339(field).map_err(|e| format!("{e}"))?;
+                Bson::DateTime(bson::DateTime::from_chrono(dt))
+            }
+            Self::ObjectId => {
+                Bson::ObjectId(ObjectId::parse_str(field).map_err(|e| format!("{e}"))?)
+            }
+        };
+        Ok(converted)
+    }
+}
+
+/// Insert value into document, dotted key is inserted as nested document.
+fn insert_path(doc: &mut Document, key: &str, val: Bson) {
+    match key.split_once('.') {
+        None => {
+            doc.insert(key, val);
+        }
+        Some((head, rest)) => {
+            if !matches!(doc.get(head), Some(Bson::Document(_))) {
+                doc.insert(head, Document::new());
+            }
+            if let Some(Bson::Document(inner)) = doc.get_mut(head) {
+                insert_path(inner, rest, val)
+            }
+        }
+    }
+}
+
+/// Get value from document, dotted key is looked up in nested documents.
+fn get_path<'a>(doc: &'a Document, key: &str) -> Option<&'a Bson> {
+    match key.split_once('.') {
+        None => doc.get(key),
+        Some((head, rest)) => match doc.get(head) {
+            Some(Bson::Document(inner)) => get_path(inner, rest),
+            _ => None,
+        },
+    }
+}
+
+/// Update of merge mode, upsert fields and `_id` are not set, because they're matched already, and
+/// `_id` can't be changed.  `_id` which is not an upsert field is kept for inserted documents.
+fn merge_update(mut doc: Document, filter: &Document, upsert_fields: &[String]) -> Document {
+    let mut update = Document::new();
+    if !upsert_fields.iter().any(|field| field == "_id")
+        && let Some(id) = doc.remove("_id")
+    {
+        update.insert("$setOnInsert", doc! {"_id": id});
+    }
+    let mut fields = Document::new();
+    set_fields(doc, upsert_fields, "", &mut fields);
+    if !fields.is_empty() {
+        update.insert("$set", fields);
+    }
+    if update.is_empty() {
+        // an update can't be empty, the document only has upsert fields.
+        update.insert("$setOnInsert", filter.clone());
+    }
+    update
+}
+
+/// Collect fields to `$set` in merge mode, upsert fields are left out.
+///
+/// Parents of nested upsert fields are set by their children, so other children are kept.
+fn set_fields(doc: Document, upsert_fields: &[String], prefix: &str, fields: &mut Document) {
+    for (k, v) in doc {
+        let path = format!("{prefix}{k}");
+        if upsert_fields.contains(&path) {
+            continue;
+        }
+        let parent = format!("{path}.");
+        match v {
+            Bson::Document(inner) if upsert_fields.iter().any(|f| f.starts_with(&parent)) => {
+                set_fields(inner, upsert_fields, &parent, fields)
+            }
+            v => {
+                fields.insert(path, v);
+            }
+        }
+    }
+}
+
+/// Max length of a bson document, the server accepts documents up to 16MiB, a little slack is
+/// allowed for documents written by other tools.
+const MAX_BSON_LEN: i32 = 16 * 1024 * 1024 + 16 * 1024;
+
+/// Failure message of an error which stops reading.
+fn stopped(e: impl std::fmt::Display) -> String {
+    format!("import stopped, remaining input is skipped: {e}")
+}
+
+fn json_to_doc(json: serde_json::Value) -> Result<Document, String> {
+    match Bson::try_from(json).map_err(|e| format!("{e}"))? {
+        Bson::Document(doc) => Ok(doc),
+        other => Err(format!(
+            "expected a document, got {:?}",
+            other.element_type()
+        )),
+    }
+}
+
+/// Read documents from the reader, `on_doc` is called with the line number and the parsed document.
+///
+/// For `ejson` and `bson` formats, the line number is the position of the document.  If the input
+/// can't be read any more, the error is reported as a failure of the line, and reading stops, so
+/// documents before it are still written.
+fn read_docs(
+    format: ImportFormat,
+    reader: Box<dyn Read>,
+    column_types: &[(String, ColumnType)],
+    bson_options: &BsonOptions,
+    mut on_doc: impl FnMut(u64, Result<Document, String>) -> Result<(), LabeledError>,
+) -> Result<(), LabeledError> {
+    match format {
+        ImportFormat::Ndjson => {
+            for (idx, line) in BufReader::new(reader).lines().enumerate() {
+                let line = match line {
+                    Ok(line) => line,
+                    // the invalid line is consumed, so the next line can be read.
+                    Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                        on_doc(idx as u64 + 1, Err(format!("{e}")))?;
+                        continue;
+                    }
+                    Err(e) => return on_doc(idx as u64 + 1, Err(stopped(e))),
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let doc = serde_json::from_str(&line)
+                    .map_err(|e| format!("{e}"))
+                    .and_then(json_to_doc);
+                on_doc(idx as u64 + 1, doc)?;
+            }
+        }
+        ImportFormat::Ejson => {
+            let mut line = 0;
+            for json in serde_json::Deserializer::from_reader(reader).into_iter() {
+                let json: serde_json::Value = match json {
+                    Ok(json) => json,
+                    // the parser can't resume after invalid json.
+                    Err(e) => return on_doc(line + 1, Err(stopped(e))),
+                };
+                let docs = match json {
+                    serde_json::Value::Array(docs) => docs,
+                    other => vec![other],
+                };
+                for json in docs {
+                    line += 1;
+                    on_doc(line, json_to_doc(json))?;
+                }
+            }
+        }
+        ImportFormat::Bson => {
+            let mut reader = BufReader::new(reader);
+            let mut line = 0;
+            // documents can't be found after a broken one, because they're not delimited.
+            loop {
+                match reader.fill_buf() {
+                    Ok([]) => break,
+                    Ok(_) => (),
+                    Err(e) => return on_doc(line + 1, Err(stopped(e))),
+                }
+                line += 1;
+                let mut len = [0u8; 4];
+                if let Err(e) = reader.read_exact(&mut len) {
+                    return on_doc(line, Err(stopped(e)));
+                }
+                let len = i32::from_le_bytes(len);
+                if len < 5 {
+                    return on_doc(
+                        line,
+                        Err(stopped(format!("invalid bson document length {len}"))),
+                    );
+                }
+                // the length may be corrupted, don't allocate a huge buffer for it.
+                if len > MAX_BSON_LEN {
+                    return on_doc(
+                        line,
+                        Err(stopped(format!("document too large, length is {len}"))),
+                    );
+                }
+                let mut buf = vec![0u8; len as usize];
+                buf[..4].copy_from_slice(&len.to_le_bytes());
+                if let Err(e) = reader.read_exact(&mut buf[4..]) {
+                    return on_doc(line, Err(stopped(e)));
+                }
+                on_doc(
+                    line,
+                    Document::from_reader(&buf[..]).map_err(|e| format!("{e}")),
+                )?;
+            }
+        }
+        ImportFormat::Csv => {
+            let mut reader = csv::Reader::from_reader(reader);
+            let headers = reader
+                .headers()
+                .map_err(|e| LabeledError::new(format!("{e}")))?
+                .clone();
+            let types: Vec<ColumnType> = headers
+                .iter()
+                .map(|h| {
+                    column_types
+                        .iter()
+                        .find(|(name, _)| name == h)
+                        .map(|(_, t)| *t)
+                        .unwrap_or(ColumnType::Auto)
+                })
+                .collect();
+            for row in reader.records() {
+                let row = match row {
+                    Ok(row) => row,
+                    Err(e) => {
+                        let line = e.position().map(|p| p.line()).unwrap_or_default();
+                        if e.is_io_error() {
+                            return on_doc(line, Err(stopped(e)));
+                        }
+                        on_doc(line, Err(format!("{e}")))?;
+                        continue;
+                    }
+                };
+                let line = row.position().map(|p| p.line()).unwrap_or_default();
+                let mut doc = Document::new();
+                let mut err = None;
+                for ((header, field), ty) in headers.iter().zip(row.iter()).zip(&types) {
+                    // blank fields are ignored.
+                    if field.is_empty() {
+                        continue;
+                    }
+                    match ty.convert(field, header, bson_options) {
+                        Ok(val) => insert_path(&mut doc, header, val),
+                        Err(e) => {
+                            err = Some(format!("invalid value of column `{header}`: {e}"));
+                            break;
+                        }
+                    }
+                }
+                on_doc(line, err.map_or(Ok(doc), Err))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write documents to collection in batches, and record the import result.
+struct Importer {
+    db: Database,
+    coll: Collection<Document>,
+    mode: ImportMode,
+    upsert_fields: Vec<String>,
+    batch_size: usize,
+    batch: Vec<(u64, Document)>,
+    inserted: u64,
+    updated: u64,
+    failures: Vec<(u64, String)>,
+    write_concern_errors: Vec<String>,
+    /// span of the collection flag, used by errors.
+    span: Span,
+}
+
+impl Importer {
+    fn push(&mut self, line: u64, doc: Result<Document, String>) -> Result<(), LabeledError> {
+        match doc {
+            Ok(doc) => {
+                self.batch.push((line, doc));
+                if self.batch.len() >= self.batch_size {
+                    self.flush()?;
+                }
+            }
+            Err(e) => self.failures.push((line, e)),
+        }
+        Ok(())
+    }
+
+    /// Write the batch, documents which can't be written are recorded as failures.
+    ///
+    /// Other errors, e.g: network errors, stop the import, the error reports what's imported
+    /// before the batch, so users know where to resume.
+    fn flush(&mut self) -> Result<(), LabeledError> {
+        let batch = std::mem::take(&mut self.batch);
+        let Some((first_line, _)) = batch.first() else {
+            return Ok(());
+        };
+        let first_line = *first_line;
+        let result = match self.mode {
+            ImportMode::Insert => self.insert(&batch),
+            ImportMode::Upsert | ImportMode::Merge => self.upsert(batch),
+        };
+        result.map_err(|e| {
+            mongo_error_at(e, self.span).with_label(
+                format!(
+                    "import stopped at the batch from line {first_line}, {} inserted, {} updated and {} failed before it",
+                    self.inserted,
+                    self.updated,
+                    self.failures.len()
+                ),
+                self.span,
+            )
+        })
+    }
+
+    fn insert(&mut self, batch: &[(u64, Document)]) -> Result<(), Error> {
+        let result = self
+            .coll
+            .insert_many(batch.iter().map(|(_, doc)| doc))
+            .ordered(false)
+            .run();
+        let e = match result {
+            Ok(result) => {
+                self.inserted += result.inserted_ids.len() as u64;
+                return Ok(());
+            }
+            Err(e) => e,
+        };
+        let ErrorKind::InsertMany(err) = e.kind.as_ref() else {
+            return Err(e);
+        };
+        let write_errors = err.write_errors.as_deref().unwrap_or_default();
+        self.inserted += (batch.len() - write_errors.len()) as u64;
+        for write_error in write_errors {
+            self.failures.push((
+                line_at(batch, write_error.index),
+                write_error.message.clone(),
+            ));
+        }
+        if let Some(wc_err) = &err.write_concern_error {
+            self.write_concern_errors.push(wc_err.message.clone());
+        }
+        Ok(())
+    }
+
+    /// Replace or merge documents by `update` commands, so a batch is written in one round trip.
+    fn upsert(&mut self, batch: Vec<(u64, Document)>) -> Result<(), Error> {
+        let mut statements = vec![];
+        for (line, doc) in batch {
+            let mut filter = Document::new();
+            for field in &self.upsert_fields {
+                match get_path(&doc, field) {
+                    Some(val) => {
+                        filter.insert(field, val.clone());
+                    }
+                    None => break,
+                }
+            }
+            if filter.len() != self.upsert_fields.len() {
+                self.failures
+                    .push((line, "document doesn't contain all upsert fields".into()));
+                continue;
+            }
+            let update = match self.mode {
+                ImportMode::Merge => merge_update(doc, &filter, &self.upsert_fields),
+                _ => doc,
+            };
+            let statement = doc! {"q": filter, "u": update, "upsert": true, "multi": false};
+            statements.push((line, statement));
+        }
+
+        let mut start = 0;
+        while start < statements.len() {
+            let mut end = start + 1;
+            let mut bytes = statement_size(&statements[start].1);
+            while end < statements.len() && end - start < MAX_BATCH_OPS {
+                bytes += statement_size(&statements[end].1);
+                if bytes > MAX_BATCH_BYTES {
+                    break;
+                }
+                end += 1;
+            }
+            self.update(&statements[start..end])?;
+            start = end;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, statements: &[(u64, Document)]) -> Result<(), Error> {
+        let mut command = doc! {
+            "update": self.coll.name(),
+            "updates": statements.iter().map(|(_, s)| s.clone()).collect::<Vec<_>>(),
+            "ordered": false,
+        };
+        if let Some(wc) = self.coll.write_concern() {
+            command.insert("writeConcern", to_bson(wc)?);
+        }
+        let reply = self.db.run_command(command).run()?;
+        let upserted = indexed_docs(&reply, "upserted").count() as u64;
+        self.inserted += upserted;
+        self.updated += count_of(&reply, "n").saturating_sub(upserted);
+        for (index, message) in write_errors(&reply) {
+            self.failures.push((line_at(statements, index), message));
+        }
+        if let Some(wc_err) = write_concern_error(&reply) {
+            self.write_concern_errors.push(wc_err);
+        }
+        Ok(())
+    }
+}
+
+/// Line of the document at the index of a batch.
+fn line_at<T>(batch: &[(u64, T)], index: usize) -> u64 {
+    batch.get(index).map(|(line, _)| *line).unwrap_or_default()
+}
+
+impl PluginCommand for Import {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc import"
+    }
+
+    fn description(&self) -> &str {
+        "import documents from extended json, ndjson, bson or csv"
+    }
+
+    fn extra_description(&self) -> &str {
+        "Documents are read from the given file, or from piped bytes if no file is given. Documents which can't be parsed or written are reported by line number, for `ejson` and `bson` formats the line number is the position of the document. If the server can't be reached, the import stops with an error telling the line to resume from."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        with_write_flags(Signature::build("mongoc import"))
+            .optional(
+                "file",
+                SyntaxShape::Filepath,
+                "file to import, read from input if not given",
+            )
+            .required_named(
+                "collection",
+                SyntaxShape::String,
+                "collection name",
+                Some('c'),
+            )
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .named(
+                "format",
+                SyntaxShape::String,
+                "import format, can be `ejson`, `ndjson`, `bson` or `csv`, default is guessed from file extension, or `ndjson`",
+                Some('f'),
+            )
+            .named(
+                "column-types",
+                SyntaxShape::Record(vec![]),
+                "csv column types, can be `auto`, `string`, `int32`, `int64`, `double`, `bool`, `date` or `oid`, default is `auto`",
+                None,
+            )
+            .named(
+                "batch-size",
+                SyntaxShape::Int,
+                "documents to write in one batch, default is 1000",
+                Some('b'),
+            )
+            .named(
+                "mode",
+                SyntaxShape::String,
+                "import mode, can be `insert`, `upsert` or `merge`, default is `insert`",
+                Some('m'),
+            )
+            .named(
+                "upsert-fields",
+                SyntaxShape::List(Box::new(SyntaxShape::String)),
+                "fields to match existing documents in `upsert` and `merge` mode, default is `[_id]`",
+                Some('u'),
+            )
+            .switch("drop", "drop the collection before import", None)
+            .input_output_types(vec![
+                (Type::Nothing, Type::record()),
+                (Type::Binary, Type::record()),
+                (Type::String, Type::record()),
+            ])
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "import a ndjson file into `students` collection",
+                example: "mongoc import -c students students.ndjson",
+                result: None,
+            },
+            Example {
+                description: "replace `students` collection with a mongodump bson file",
+                example: "open --raw students.bson | mongoc import -c students -f bson --drop",
+                result: None,
+            },
+            Example {
+                description: "import a csv file with typed columns, merge into documents with the same `name`",
+                example: "mongoc import -c teachers teachers.csv --column-types {age: int32, born: date} -m merge -u [name]",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &EngineInterface,
+        call: &EvaluatedCall,
+        input: PipelineData,
+    ) -> Result<PipelineData, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (handle_id, plugin.get_handle(handle_id, call.head)?)
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (handle_id, plugin.get_handle(handle_id, db_handle.span())?)
+            }
+        };
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
+        let coll: String = call
+            .get_flag("collection")?
+            .expect("already check existed.");
+        let file: Option<Spanned<String>> = call.opt(0)?;
+        let format: Option<Spanned<String>> = call.get_flag("format")?;
+        let format = match (format, &file) {
+            (Some(format), _) => ImportFormat::parse(&format)?,
+            (None, Some(file)) => ImportFormat::from_path(&file.item),
+            (None, None) => ImportFormat::Ndjson,
+        };
+        let mode = match call.get_flag::<Spanned<String>>("mode")? {
+            Some(mode) => ImportMode::parse(&mode)?,
+            None => ImportMode::Insert,
+        };
+        let batch_size: Spanned<i64> = call.get_flag("batch-size")?.unwrap_or(Spanned {
+            item: 1000,
+            span: call.head,
+        });
+        if batch_size.item <= 0 {
+            return Err(LabeledError::new("get invalid number")
+                .with_label("should be positive", batch_size.span));
+        }
+        let upsert_fields: Vec<String> = call
+            .get_flag("upsert-fields")?
+            .unwrap_or_else(|| vec!["_id".to_string()]);
+        let mut column_types = vec![];
+        if let Some(types) = call.get_flag::<Record>("column-types")? {
+            for (name, ty) in types.into_iter() {
+                let span = ty.span();
+                column_types.push((name, ColumnType::parse(&ty.coerce_into_string()?, span)?));
+            }
+        }
+
+        let reader: Box<dyn Read> = match file {
+            Some(file) => {
+                let path = Path::new(&engine.get_current_dir()?).join(&file.item);
+                Box::new(std::fs::File::open(path).map_err(|e| {
+                    LabeledError::new(format!("{e}")).with_label("can't open file", file.span)
+                })?)
+            }
+            None => match input {
+                PipelineData::ByteStream(stream, ..) => match stream.reader() {
+                    Some(reader) => Box::new(reader),
+                    None => Box::new(std::io::empty()),
+                },
+                PipelineData::Value(Value::Binary { val, .. }, ..) => Box::new(Cursor::new(val)),
+                PipelineData::Value(Value::String { val, .. }, ..) => {
+                    Box::new(Cursor::new(val.into_bytes()))
+                }
+                other => {
+                    return Err(LabeledError::new("invalid input").with_label(
+                        format!(
+                            "expected a file or binary/string input, got {}",
+                            other.get_type()
+                        ),
+                        call.head,
+                    ));
+                }
+            },
+        };
+
+        let coll = db.collection_with_options::<Document>(&coll, collection_options(call)?);
+        if call.has_flag("drop")? {
+            coll.drop().run().map_err(|e| mongo_error(e, call))?;
+            plugin.invalidate_completions(handle_id, None);
+        }
+        let mut importer = Importer {
+            db,
+            coll,
+            mode,
+            upsert_fields,
+            batch_size: batch_size.item as usize,
+            batch: vec![],
+            inserted: 0,
+            updated: 0,
+            failures: vec![],
+            write_concern_errors: vec![],
+            span: call
+                .get_flag_value("collection")
+                .map(|c| c.span())
+                .unwrap_or(call.head),
+        };
+        read_docs(format, reader, &column_types, &bson_options, |line, doc| {
+            importer.push(line, doc)
+        })?;
+        importer.flush()?;
+        // the collection may be created by the import.
+        plugin.invalidate_completions(handle_id, None);
+
+        importer.failures.sort_by_key(|(line, _)| *line);
+        let failures = importer
+            .failures
+            .iter()
+            .map(|(line, err)| {
+                Value::record(
+                    record! {
+                        "line" => Value::int(*line as i64, call.head),
+                        "error" => Value::string(err, call.head),
+                    },
+                    call.head,
+                )
+            })
+            .collect();
+        Ok(PipelineData::Value(
+            Value::record(
+                record! {
+                    "inserted" => Value::int(importer.inserted as i64, call.head),
+                    "updated" => Value::int(importer.updated as i64, call.head),
+                    "failed" => Value::int(importer.failures.len() as i64, call.head),
+                    "failures" => Value::list(failures, call.head),
+                    "write_concern_errors" => Value::list(
+                        importer
+                            .write_concern_errors
+                            .into_iter()
+                            .map(|e| Value::string(e, call.head))
+                            .collect(),
+                        call.head,
+                    ),
+                },
+                call.head,
+            ),
+            None,
+        ))
+    }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "collection" => {
+                super::get_collection_names_at_handle(plugin, engine, &call)
+            }
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read documents from bytes, returns parsed documents or failures by line.
+    fn read(
+        format: ImportFormat,
+        input: &[u8],
+        column_types: &[(String, ColumnType)],
+    ) -> Vec<(u64, Result<Document, String>)> {
+        let mut docs = vec![];
+        read_docs(
+            format,
+            Box::new(Cursor::new(input.to_vec())),
+            column_types,
+            &BsonOptions::default(),
+            |line, doc| {
+                docs.push((line, doc));
+                Ok(())
+            },
+        )
+        .unwrap();
+        docs
+    }
+
+    /// Get the failure message, panics if the document is parsed.
+    fn failure(doc: &(u64, Result<Document, String>)) -> &str {
+        doc.1.as_ref().expect_err("document should fail")
+    }
+
+    fn bson_bytes(docs: &[Document]) -> Vec<u8> {
+        let mut bytes = vec![];
+        for doc in docs {
+            doc.to_writer(&mut bytes).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn bson_documents_are_read_in_order() {
+        let input = bson_bytes(&[doc! { "a": 1 }, doc! { "b": "x" }]);
+        let docs = read(ImportFormat::Bson, &input, &[]);
+        assert_eq!(
+            docs,
+            vec![(1, Ok(doc! { "a": 1 })), (2, Ok(doc! { "b": "x" }))]
+        );
+    }
+
+    #[test]
+    fn bson_corrupt_length_stops_without_allocating() {
+        let mut input = bson_bytes(&[doc! { "a": 1 }]);
+        // a length prefix of about 2GiB, followed by a few bytes.
+        input.extend_from_slice(&i32::MAX.to_le_bytes());
+        input.extend_from_slice(b"\x10a\x00");
+        let docs = read(ImportFormat::Bson, &input, &[]);
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0], (1, Ok(doc! { "a": 1 })));
+        assert_eq!(docs[1].0, 2);
+        assert!(failure(&docs[1]).contains("document too large"));
+    }
+
+    #[test]
+    fn bson_invalid_and_truncated_documents_stop_reading() {
+        let docs = read(ImportFormat::Bson, &3i32.to_le_bytes(), &[]);
+        assert!(failure(&docs[0]).contains("invalid bson document length 3"));
+
+        let mut input = bson_bytes(&[doc! { "a": 1 }, doc! { "b": 2 }]);
+        input.truncate(input.len() - 3);
+        let docs = read(ImportFormat::Bson, &input, &[]);
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0], (1, Ok(doc! { "a": 1 })));
+        assert_eq!(docs[1].0, 2);
+        assert!(failure(&docs[1]).starts_with("import stopped"));
+    }
+
+    #[test]
+    fn merge_update_leaves_out_id_and_upsert_fields() {
+        let doc = doc! { "_id": 1, "name": "a", "age": 3 };
+        let filter = doc! { "name": "a" };
+        assert_eq!(
+            merge_update(doc, &filter, &["name".to_string()]),
+            doc! { "$setOnInsert": { "_id": 1 }, "$set": { "age": 3 } }
+        );
+
+        let doc = doc! { "_id": 1, "age": 3 };
+        let filter = doc! { "_id": 1 };
+        assert_eq!(
+            merge_update(doc, &filter, &["_id".to_string()]),
+            doc! { "$set": { "age": 3 } }
+        );
+    }
+
+    #[test]
+    fn merge_update_sets_siblings_of_nested_upsert_fields() {
+        let doc = doc! { "address": { "city": "x", "zip": 1 }, "n": 2 };
+        let filter = doc! { "address.city": "x" };
+        assert_eq!(
+            merge_update(doc, &filter, &["address.city".to_string()]),
+            doc! { "$set": { "address.zip": 1, "n": 2 } }
+        );
+    }
+
+    #[test]
+    fn merge_update_of_upsert_fields_only_is_not_empty() {
+        let fields = ["name".to_string()];
+        let filter = doc! { "name": "a" };
+        assert_eq!(
+            merge_update(doc! { "name": "a" }, &filter, &fields),
+            doc! { "$setOnInsert": { "name": "a" } }
+        );
+        assert_eq!(
+            merge_update(doc! { "_id": 1, "name": "a" }, &filter, &fields),
+            doc! { "$setOnInsert": { "_id": 1 } }
+        );
+    }
+
+    #[test]
+    fn column_types_are_parsed() {
+        let span = Span::test_data();
+        assert!(matches!(
+            ColumnType::parse("int", span),
+            Ok(ColumnType::Int64)
+        ));
+        assert!(matches!(
+            ColumnType::parse("int32", span),
+            Ok(ColumnType::Int32)
+        ));
+        assert!(matches!(
+            ColumnType::parse("float", span),
+            Ok(ColumnType::Double)
+        ));
+        assert!(matches!(
+            ColumnType::parse("objectid", span),
+            Ok(ColumnType::ObjectId)
+        ));
+        assert!(ColumnType::parse("decimal", span).is_err());
+    }
+
+    #[test]
+    fn column_types_convert_fields() {
+        let options = BsonOptions::default();
+        let convert = |ty: ColumnType, field: &str| ty.convert(field, "f", &options);
+        assert_eq!(convert(ColumnType::Auto, "12"), Ok(Bson::Int32(12)));
+        assert_eq!(
+            convert(ColumnType::Auto, "5000000000"),
+            Ok(Bson::Int64(5_000_000_000))
+        );
+        assert_eq!(convert(ColumnType::Auto, "1.5"), Ok(Bson::Double(1.5)));
+        assert_eq!(convert(ColumnType::Auto, "true"), Ok(Bson::Boolean(true)));
+        assert_eq!(
+            convert(ColumnType::Auto, "abc"),
+            Ok(Bson::String("abc".into()))
+        );
+        assert_eq!(
+            convert(ColumnType::String, "12"),
+            Ok(Bson::String("12".into()))
+        );
+        assert_eq!(convert(ColumnType::Int64, "12"), Ok(Bson::Int64(12)));
+        assert!(convert(ColumnType::Int32, "5000000000").is_err());
+        assert!(convert(ColumnType::Bool, "yes").is_err());
+        assert_eq!(
+            convert(ColumnType::Date, "2024-01-02T03:04:05Z"),
+            Ok(Bson::DateTime(bson::DateTime::from_millis(
+                1_704_164_645_000
+            )))
+        );
+        assert_eq!(
+            convert(ColumnType::ObjectId, "65a1b2c3d4e5f60718293a4b"),
+            Ok(Bson::ObjectId(
+                ObjectId::parse_str("65a1b2c3d4e5f60718293a4b").unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn csv_rows_use_column_types() {
+        let input = b"name,age,address.city\na,3,x\nb,,y\nc,old,z\n";
+        let types = [("age".to_string(), ColumnType::Int64)];
+        let docs = read(ImportFormat::Csv, input, &types);
+        assert_eq!(docs.len(), 3);
+        assert_eq!(
+            docs[0],
+            (
+                2,
+                Ok(doc! { "name": "a", "age": 3i64, "address": { "city": "x" } })
+            )
+        );
+        // blank fields are left out.
+        assert_eq!(
+            docs[1],
+            (3, Ok(doc! { "name": "b", "address": { "city": "y" } }))
+        );
+        assert_eq!(docs[2].0, 4);
+        assert!(failure(&docs[2]).starts_with("invalid value of column `age`"));
+    }
+}
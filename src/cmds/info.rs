@@ -0,0 +1,153 @@
+use crate::MongoPlugin;
+use crate::error::mongo_error;
+use mongodb::bson::{Bson, Document, doc};
+use mongodb::error::{Error, ErrorKind};
+use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Signature, Span, SyntaxShape, Type, Value,
+    engine::ArgType, record,
+};
+use std::time::Instant;
+
+pub struct Info;
+
+impl SimplePluginCommand for Info {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc info"
+    }
+
+    fn description(&self) -> &str {
+        "show mongodb server information of a handle"
+    }
+
+    fn extra_description(&self) -> &str {
+        "The information is collected by running `hello` and `buildInfo` commands on the server."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc info")
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .input_output_type(Type::Nothing, Type::record())
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "show server information of current database handle",
+                example: "mongoc info",
+                result: None,
+            },
+            Example {
+                description: "show server version of database handle `prod`",
+                example: "mongoc info -d prod | get version",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let db = match db_handle {
+            None => plugin.get_handle(plugin.get_current()?, call.head)?,
+            Some(db_handle) => {
+                plugin.get_handle(plugin.resolve_handle_id(&db_handle)?, db_handle.span())?
+            }
+        };
+        let mut start = Instant::now();
+        let hello = match db.run_command(doc! {"hello": 1}).run() {
+            // `hello` is not available before mongodb 4.4.2, fallback to legacy `isMaster`.
+            Err(e) if is_command_not_found(&e) => {
+                start = Instant::now();
+                db.run_command(doc! {"isMaster": 1}).run()
+            }
+            other => other,
+        }
+        .map_err(|e| mongo_error(e, call))?;
+        let latency = start.elapsed();
+        let build_info = db
+            .run_command(doc! {"buildInfo": 1})
+            .run()
+            .map_err(|e| mongo_error(e, call))?;
+
+        let span = call.head;
+        let topology = if hello.contains_key("setName") {
+            "replica_set"
+        } else if hello.get_str("msg") == Ok("isdbgrid") {
+            "sharded"
+        } else {
+            "single"
+        };
+        let writable = hello
+            .get_bool("isWritablePrimary")
+            .or_else(|_| hello.get_bool("ismaster"))
+            .unwrap_or(false);
+        Ok(Value::record(
+            record! {
+                "version" => opt_string(&build_info, "version", span),
+                "topology" => Value::string(topology, span),
+                "set_name" => opt_string(&hello, "setName", span),
+                "primary" => opt_string(&hello, "primary", span),
+                "hosts" => match hello.get_array("hosts") {
+                    Err(_) => Value::list(vec![], span),
+                    Ok(hosts) => Value::list(
+                        hosts
+                            .iter()
+                            .filter_map(|h| h.as_str())
+                            .map(|h| Value::string(h, span))
+                            .collect(),
+                        span,
+                    ),
+                },
+                "latency" => Value::duration(latency.as_nanos() as i64, span),
+                "max_wire_version" => match hello.get("maxWireVersion") {
+                    Some(Bson::Int32(v)) => Value::int(i64::from(*v), span),
+                    Some(Bson::Int64(v)) => Value::int(*v, span),
+                    _ => Value::nothing(span),
+                },
+                "writable" => Value::bool(writable, span),
+            },
+            span,
+        ))
+    }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        _engine: &EngineInterface,
+        _call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
+
+fn opt_string(doc: &Document, key: &str, span: Span) -> Value {
+    match doc.get_str(key) {
+        Ok(s) => Value::string(s, span),
+        Err(_) => Value::nothing(span),
+    }
+}
+
+/// Check if the server doesn't know the command, error code 59 is `CommandNotFound`.
+fn is_command_not_found(e: &Error) -> bool {
+    matches!(e.kind.as_ref(), ErrorKind::Command(err) if err.code == 59)
+}
@@ -0,0 +1,66 @@
+use super::next::{cursor_id, get_cursor_ids};
+use crate::MongoPlugin;
+use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Signature, SyntaxShape, Type, Value,
+    engine::ArgType,
+};
+
+pub struct KillCursor;
+
+impl SimplePluginCommand for KillCursor {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc kill-cursor"
+    }
+
+    fn description(&self) -> &str {
+        "close a cursor kept by `mongoc find --keep-cursor`"
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc kill-cursor")
+            .required(
+                "cursor-id",
+                SyntaxShape::Int,
+                "cursor id to close, can get from `mongoc cursors`",
+            )
+            .input_output_type(Type::Nothing, Type::Nothing)
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![Example {
+            description: "close cursor 0",
+            example: "mongoc kill-cursor 0",
+            result: None,
+        }]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let id = cursor_id(call)?;
+        plugin.cursors.remove(id.item, id.span)?;
+        Ok(Value::nothing(call.head))
+    }
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        _engine: &EngineInterface,
+        _call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Positional(0) => get_cursor_ids(plugin),
+            _ => None,
+        }
+    }
+}
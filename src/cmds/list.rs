@@ -1,6 +1,6 @@
 use crate::MongoPlugin;
-use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
-use nu_protocol::{Category, LabeledError, Signature, Type, Value, record};
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{Category, Example, LabeledError, Signature, Span, Type, Value, record};
 
 pub struct List;
 impl SimplePluginCommand for List {
@@ -16,16 +16,34 @@ impl SimplePluginCommand for List {
 
     fn signature(&self) -> nu_protocol::Signature {
         Signature::build("mongoc list")
+            .switch(
+                "check",
+                "ping every handle, and add a `status` column",
+                None,
+            )
             .category(Category::Database)
             .input_output_type(
                 Type::Nothing,
-                Type::List(Box::new(Type::Record(Box::new([
-                    ("id".to_string(), Type::Int),
-                    ("addr".to_string(), Type::String),
-                ])))),
+                // `status` is only added by `--check`.
+                Type::OneOf(Box::new([handles_type(false), handles_type(true)])),
             )
     }
 
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "list opened database handles",
+                example: "mongoc list",
+                result: None,
+            },
+            Example {
+                description: "list opened database handles, and check if they still work",
+                example: "mongoc list --check",
+                result: None,
+            },
+        ]
+    }
+
     fn run(
         &self,
         plugin: &MongoPlugin,
@@ -35,16 +53,65 @@ impl SimplePluginCommand for List {
     ) -> Result<Value, LabeledError> {
         let mut handles = plugin.list_handles();
         handles.sort_by_key(|k| k.0);
+        let statuses = if call.has_flag("check")? {
+            Some(check_handles(plugin, &handles))
+        } else {
+            None
+        };
         let mut result = vec![];
-        for (id, conn_str) in handles {
-            result.push(Value::record(
-                record! {
+        for (idx, (id, name, conn_str)) in handles.into_iter().enumerate() {
+            let mut row = record! {
                 "id" => Value::int(id.into(), call.head),
-                "addr" => Value::string(conn_str, call.head)
+                "name" => match name {
+                    None => Value::nothing(call.head),
+                    Some(name) => Value::string(name, call.head),
                 },
-                call.head,
-            ))
+                "addr" => Value::string(conn_str, call.head),
+            };
+            if let Some(statuses) = &statuses {
+                row.push("status", Value::string(statuses[idx].clone(), call.head));
+            }
+            result.push(Value::record(row, call.head))
         }
         Ok(Value::list(result, call.head))
     }
 }
+
+/// Type of listed handles, `name` is nothing if the handle has no name.
+fn handles_type(check: bool) -> Type {
+    let mut columns = vec![
+        ("id".to_string(), Type::Int),
+        (
+            "name".to_string(),
+            Type::OneOf(Box::new([Type::String, Type::Nothing])),
+        ),
+        ("addr".to_string(), Type::String),
+    ];
+    if check {
+        columns.push(("status".to_string(), Type::String));
+    }
+    Type::List(Box::new(Type::Record(columns.into())))
+}
+
+/// Ping all handles concurrently, returns `ok` or the error message for each handle.
+fn check_handles(plugin: &MongoPlugin, handles: &[(u32, Option<String>, String)]) -> Vec<String> {
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = handles
+            .iter()
+            .map(|(id, _, _)| {
+                let db = plugin.get_handle(*id, Span::unknown());
+                scope.spawn(move || match db {
+                    Err(e) => e.msg,
+                    Ok(db) => match super::ping_db(&db) {
+                        Ok(_) => "ok".to_string(),
+                        Err(e) => format!("{e}"),
+                    },
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|w| w.join().unwrap_or_else(|_| "check failed".to_string()))
+            .collect()
+    })
+}
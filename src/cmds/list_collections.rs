@@ -1,8 +1,9 @@
 use crate::MongoPlugin;
+use crate::error::mongo_error;
 use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
 use nu_protocol::{
-    Category, DynamicSuggestion, Example, LabeledError, Signature, Spanned, SyntaxShape, Type,
-    Value, engine::ArgType,
+    Category, DynamicSuggestion, Example, LabeledError, Signature, SyntaxShape, Type, Value,
+    engine::ArgType,
 };
 
 pub struct ListCollectionNames;
@@ -22,15 +23,15 @@ impl SimplePluginCommand for ListCollectionNames {
         Signature::build("mongoc list-colls")
             .named(
                 "db-handle",
-                SyntaxShape::Int,
-                "database handle, can get from `mongoc list`",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
                 Some('d'),
             )
             .input_output_type(Type::Nothing, Type::table())
             .category(Category::Database)
     }
 
-    fn examples(&self) -> Vec<Example> {
+    fn examples(&self) -> Vec<Example<'_>> {
         vec![
             Example {
                 description: "list collection names under current database handle",
@@ -51,19 +52,36 @@ impl SimplePluginCommand for ListCollectionNames {
         call: &nu_plugin::EvaluatedCall,
         _input: &Value,
     ) -> Result<Value, LabeledError> {
-        let db_handle: Option<Spanned<i64>> = call.get_flag("db-handle")?;
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
         let db = match db_handle {
             None => plugin.get_handle(plugin.get_current()?, call.head)?,
-            Some(db_handle) => plugin.get_handle(db_handle.item as u8, db_handle.span)?,
+            Some(db_handle) => {
+                plugin.get_handle(plugin.resolve_handle_id(&db_handle)?, db_handle.span())?
+            }
         };
         let result = db
             .list_collection_names()
             .run()
-            .map_err(|e| LabeledError::new(format!("{e}")))?;
+            .map_err(|e| mongo_error(e, call))?;
         let mut rows = vec![];
         for name in result {
             rows.push(Value::string(name, call.head))
         }
         Ok(Value::list(rows, call.head))
     }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        _engine: &EngineInterface,
+        _call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
 }
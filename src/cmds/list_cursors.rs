@@ -0,0 +1,69 @@
+use crate::MongoPlugin;
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{Category, Example, LabeledError, Signature, Type, Value, record};
+
+pub struct ListCursors;
+
+impl SimplePluginCommand for ListCursors {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc cursors"
+    }
+
+    fn description(&self) -> &str {
+        "list cursors kept by `mongoc find --keep-cursor`"
+    }
+
+    fn extra_description(&self) -> &str {
+        "Cursors which are idle for 9 minutes are closed, before the server kills them, `mongoc next` reports them as expired."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc cursors")
+            .input_output_type(
+                Type::Nothing,
+                Type::List(Box::new(Type::Record(Box::new([
+                    ("id".to_string(), Type::Int),
+                    ("handle".to_string(), Type::Int),
+                    ("collection".to_string(), Type::String),
+                    ("batch_size".to_string(), Type::Int),
+                    ("fetched".to_string(), Type::Int),
+                    ("idle".to_string(), Type::Duration),
+                ])))),
+            )
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![Example {
+            description: "list kept cursors",
+            example: "mongoc cursors",
+            result: None,
+        }]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let span = call.head;
+        let rows = plugin.cursors.list(|id, entry| {
+            Value::record(
+                record! {
+                    "id" => Value::int(id.into(), span),
+                    "handle" => Value::int(entry.handle.into(), span),
+                    "collection" => Value::string(entry.coll.clone(), span),
+                    "batch_size" => Value::int(entry.batch_size as i64, span),
+                    "fetched" => Value::int(entry.fetched as i64, span),
+                    "idle" => Value::duration(entry.last_used.elapsed().as_nanos() as i64, span),
+                },
+                span,
+            )
+        });
+        Ok(Value::list(rows, span))
+    }
+}
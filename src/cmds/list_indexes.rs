@@ -1,10 +1,11 @@
 use crate::MongoPlugin;
+use crate::error::mongo_error;
 use bson::ser::to_document;
 use mongodb::bson::Document;
 use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
 use nu_protocol::{
-    Category, DynamicSuggestion, Example, LabeledError, Signature, Spanned, SyntaxShape, Type,
-    Value, engine::ArgType, record,
+    Category, DynamicSuggestion, Example, LabeledError, Signature, SyntaxShape, Type, Value,
+    engine::ArgType, record,
 };
 
 pub struct ListIndexes;
@@ -30,15 +31,15 @@ impl SimplePluginCommand for ListIndexes {
             )
             .named(
                 "db-handle",
-                SyntaxShape::Int,
-                "database handle, can get from `mongoc list`",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
                 Some('d'),
             )
             .input_output_type(Type::Nothing, Type::table())
             .category(Category::Database)
     }
 
-    fn examples(&self) -> Vec<Example> {
+    fn examples(&self) -> Vec<Example<'_>> {
         vec![Example {
             description: "list indexes for collection `students`",
             example: "mongoc list-indexes students",
@@ -52,10 +53,12 @@ impl SimplePluginCommand for ListIndexes {
         call: &nu_plugin::EvaluatedCall,
         _input: &Value,
     ) -> Result<Value, LabeledError> {
-        let db_handle: Option<Spanned<i64>> = call.get_flag("db-handle")?;
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
         let db = match db_handle {
             None => plugin.get_handle(plugin.get_current()?, call.head)?,
-            Some(db_handle) => plugin.get_handle(db_handle.item as u8, db_handle.span)?,
+            Some(db_handle) => {
+                plugin.get_handle(plugin.resolve_handle_id(&db_handle)?, db_handle.span())?
+            }
         };
         let coll: String = call
             .get_flag("collection")?
@@ -64,11 +67,11 @@ impl SimplePluginCommand for ListIndexes {
             .collection::<Document>(&coll)
             .list_indexes()
             .run()
-            .map_err(|e| LabeledError::new(format!("{e}")))?;
+            .map_err(|e| mongo_error(e, call))?;
 
         let mut rows = vec![];
         for doc in result {
-            let doc = doc.map_err(|e| LabeledError::new(format!("{e}")))?;
+            let doc = doc.map_err(|e| mongo_error(e, call))?;
             let rec = Value::record(
                 record! {
                     "key" => Value::string(doc.keys.to_string(), call.head),
@@ -83,18 +86,20 @@ impl SimplePluginCommand for ListIndexes {
         }
         Ok(Value::list(rows, call.head))
     }
+    #[allow(deprecated)]
     fn get_dynamic_completion(
         &self,
         plugin: &Self::Plugin,
-        _engine: &EngineInterface,
-        _call: DynamicCompletionCall,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
         arg_type: ArgType,
         _experimental: nu_protocol::engine::ExperimentalMarker,
     ) -> Option<Vec<DynamicSuggestion>> {
         match arg_type {
             ArgType::Flag(name) if name == "collection" => {
-                super::get_collection_names_at_current_handle(plugin)
+                super::get_collection_names_at_handle(plugin, engine, &call)
             }
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
             _ => None,
         }
     }
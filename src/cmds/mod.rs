@@ -1,32 +1,82 @@
+mod bulk_write;
+mod coll_mod;
+mod concern;
 mod count;
+mod create_coll;
 mod current_coll_names;
+mod decimal;
 mod delete_many;
 mod delete_one;
 mod drop;
 mod estimated;
+mod export;
+mod field_names;
 mod find;
 mod find_one;
+mod forget;
+mod gridfs;
+mod gridfs_delete;
+mod gridfs_get;
+mod gridfs_list;
+mod gridfs_put;
+mod gridfs_rename;
+mod handle_ids;
+mod import;
+mod info;
+mod kill_cursor;
 mod list;
 mod list_collections;
+mod list_cursors;
 mod list_indexes;
 mod mongoc;
+mod next;
+mod oid;
 mod open;
+mod ping;
+mod query_dsl;
 mod remove;
+mod rename_coll;
+mod schema;
 mod select_handle;
+mod uuid;
 mod val_converter;
 
+pub use bulk_write::*;
+pub use coll_mod::*;
 pub use count::*;
-pub(super) use current_coll_names::get_collection_names_at_current_handle;
+pub use create_coll::*;
+pub(super) use current_coll_names::get_collection_names_at_handle;
+pub use decimal::*;
 pub use delete_many::*;
 pub use delete_one::*;
 pub use drop::*;
 pub use estimated::*;
+pub use export::*;
+pub(super) use field_names::get_field_names_at_handle;
 pub use find::*;
 pub use find_one::*;
+pub use forget::*;
+pub use gridfs::GridFsCmd;
+pub use gridfs_delete::*;
+pub use gridfs_get::*;
+pub use gridfs_list::*;
+pub use gridfs_put::*;
+pub use gridfs_rename::*;
+pub(super) use handle_ids::{get_handle_ids, handle_from_call};
+pub use import::*;
+pub use info::*;
+pub use kill_cursor::*;
 pub use list::*;
 pub use list_collections::*;
+pub use list_cursors::*;
 pub use list_indexes::*;
 pub use mongoc::*;
+pub use next::*;
+pub use oid::*;
 pub use open::*;
+pub use ping::*;
 pub use remove::*;
+pub use rename_coll::*;
+pub use schema::*;
 pub use select_handle::*;
+pub use uuid::*;
@@ -0,0 +1,148 @@
+use super::val_converter::{BsonOptions, doc_to_value};
+use crate::MongoPlugin;
+use crate::error::mongo_error;
+use mongodb::bson::Document;
+use mongodb::sync::Cursor;
+use nu_plugin::{DynamicCompletionCall, EngineInterface, EvaluatedCall, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Signature, Spanned, SyntaxShape, Type,
+    Value, engine::ArgType,
+};
+
+pub struct Next;
+
+impl SimplePluginCommand for Next {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc next"
+    }
+
+    fn description(&self) -> &str {
+        "fetch next batch of documents from a cursor kept by `mongoc find --keep-cursor`"
+    }
+
+    fn extra_description(&self) -> &str {
+        "The cursor is closed after all documents are fetched, or after it is idle for 9 minutes."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc next")
+            .required(
+                "cursor-id",
+                SyntaxShape::Int,
+                "cursor id, can get from `mongoc cursors`",
+            )
+            .named(
+                "limit",
+                SyntaxShape::Int,
+                "limit rows to return, default is the limit of `mongoc find`",
+                Some('l'),
+            )
+            .input_output_type(Type::Nothing, Type::table())
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "fetch next batch of `students`",
+                example: "let page = mongoc find -c students --keep-cursor; mongoc next $page.cursor",
+                result: None,
+            },
+            Example {
+                description: "fetch next 100 documents from cursor 0",
+                example: "mongoc next 0 -l 100",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let id = cursor_id(call)?;
+        let limit: Option<Spanned<i64>> = call.get_flag("limit")?;
+        let (mut cursor, handle_id, batch_size) = plugin.cursors.take(id.item, id.span)?;
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
+        let batch_size = match limit {
+            None => batch_size,
+            Some(limit) => u64::try_from(limit.item).map_err(|_| {
+                LabeledError::new("get invalid number").with_label("can't be negative", limit.span)
+            })?,
+        };
+        match fetch_batch(&mut cursor, batch_size, call, &bson_options) {
+            Ok((rows, exhausted)) => {
+                plugin
+                    .cursors
+                    .put_back(id.item, cursor, rows.len() as u64, exhausted);
+                Ok(Value::list(rows, call.head))
+            }
+            Err(e) => {
+                // the cursor can't be used after errors.
+                plugin.cursors.put_back(id.item, cursor, 0, true);
+                Err(e)
+            }
+        }
+    }
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        _engine: &EngineInterface,
+        _call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Positional(0) => get_cursor_ids(plugin),
+            _ => None,
+        }
+    }
+}
+
+/// Fetch at most `size` documents, returns them with whether the cursor is exhausted.
+pub(super) fn fetch_batch(
+    cursor: &mut Cursor<Document>,
+    size: u64,
+    call: &EvaluatedCall,
+    bson_options: &BsonOptions,
+) -> Result<(Vec<Value>, bool), LabeledError> {
+    let mut rows = vec![];
+    while (rows.len() as u64) < size {
+        match cursor.next() {
+            None => return Ok((rows, true)),
+            Some(doc) => rows.push(doc_to_value(
+                doc.map_err(|e| mongo_error(e, call))?,
+                call.head,
+                bson_options,
+            )),
+        }
+    }
+    Ok((rows, false))
+}
+
+pub(super) fn cursor_id(call: &EvaluatedCall) -> Result<Spanned<u32>, LabeledError> {
+    let id: Spanned<i64> = call.req(0)?;
+    let item = u32::try_from(id.item).map_err(|_| {
+        LabeledError::new("cursor id out of range")
+            .with_label(format!("should be between 0 and {}", u32::MAX), id.span)
+    })?;
+    Ok(Spanned {
+        item,
+        span: id.span,
+    })
+}
+
+/// Get all cursor ids, with collection name as description.
+pub(super) fn get_cursor_ids(plugin: &MongoPlugin) -> Option<Vec<DynamicSuggestion>> {
+    Some(plugin.cursors.list(|id, entry| DynamicSuggestion {
+        value: id.to_string(),
+        description: Some(format!("{} of handle {}", entry.coll, entry.handle)),
+        ..Default::default()
+    }))
+}
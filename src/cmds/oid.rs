@@ -0,0 +1,66 @@
+use crate::MongoPlugin;
+use mongodb::bson::oid::ObjectId;
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{
+    Category, Example, LabeledError, Signature, Spanned, SyntaxShape, Type, Value, record,
+};
+
+pub struct Oid;
+
+impl SimplePluginCommand for Oid {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc oid"
+    }
+
+    fn description(&self) -> &str {
+        "make an ObjectId literal, which can be used in query and documents"
+    }
+
+    fn extra_description(&self) -> &str {
+        "The literal is a record like `{$oid: 65a1b2c3d4e5f60718293a4b}`, a new ObjectId is generated if hex string is not given."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc oid")
+            .optional("hex", SyntaxShape::String, "hex string of the ObjectId")
+            .input_output_type(Type::Nothing, Type::record())
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "find a student by `_id`",
+                example: "mongoc find-one {_id: (mongoc oid 65a1b2c3d4e5f60718293a4b)} -c students",
+                result: None,
+            },
+            Example {
+                description: "generate a new ObjectId",
+                example: "mongoc oid",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        _plugin: &MongoPlugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let hex: Option<Spanned<String>> = call.opt(0)?;
+        let oid = match hex {
+            None => ObjectId::new(),
+            Some(hex) => ObjectId::parse_str(&hex.item).map_err(|e| {
+                LabeledError::new("invalid ObjectId").with_label(format!("{e}"), hex.span)
+            })?,
+        };
+        Ok(Value::record(
+            record! { "$oid" => Value::string(oid.to_hex(), call.head) },
+            call.head,
+        ))
+    }
+}
@@ -1,6 +1,16 @@
+use super::concern::{
+    read_concern, read_preference, with_read_flags, with_write_flags, write_concern,
+};
+use super::val_converter::{BsonOptions, value_to_doc};
 use crate::MongoPlugin;
-use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
-use nu_protocol::{Category, Example, LabeledError, Signature, SyntaxShape, Type, Value};
+use crate::connect_options::{ConnectOptions, compressor, uuid_representation};
+use crate::session::strip_password;
+use nu_plugin::{EngineInterface, EvaluatedCall, SimplePluginCommand};
+use nu_protocol::{
+    Category, Example, LabeledError, Record, Signature, Spanned, SyntaxShape, Type, Value,
+};
+use std::path::PathBuf;
+use std::time::Duration;
 
 pub struct Open;
 
@@ -16,18 +26,171 @@ impl SimplePluginCommand for Open {
     }
 
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("mongoc open")
+        let sig = Signature::build("mongoc open")
             .required("mongo-url", SyntaxShape::String, "mongodb url to connect")
+            .named(
+                "name",
+                SyntaxShape::String,
+                "name of the handle, can be used in place of handle id",
+                Some('n'),
+            )
+            .named("user", SyntaxShape::String, "user name to authenticate", Some('u'))
+            .named(
+                "password-env",
+                SyntaxShape::String,
+                "environment variable which contains the password",
+                None,
+            )
+            .switch(
+                "password-prompt",
+                "read the password from terminal",
+                None,
+            )
+            .named(
+                "auth-source",
+                SyntaxShape::String,
+                "database to authenticate against",
+                None,
+            )
+            .named(
+                "auth-mechanism",
+                SyntaxShape::String,
+                "authentication mechanism, can be SCRAM-SHA-1, SCRAM-SHA-256, MONGODB-X509, MONGODB-AWS, MONGODB-OIDC or PLAIN",
+                None,
+            )
+            .named(
+                "auth-properties",
+                SyntaxShape::Record(vec![]),
+                "authentication mechanism properties, e.g: {ENVIRONMENT: azure, TOKEN_RESOURCE: <resource>} for MONGODB-OIDC",
+                None,
+            )
+            .named(
+                "connect-timeout",
+                SyntaxShape::Duration,
+                "how long to wait for the server when opening connection, default is 10sec",
+                None,
+            )
+            .named(
+                "server-selection-timeout",
+                SyntaxShape::Duration,
+                "how long to wait for a suitable server before each operation fails",
+                None,
+            )
+            .named(
+                "max-pool-size",
+                SyntaxShape::Int,
+                "max connections to each server",
+                None,
+            )
+            .named(
+                "app-name",
+                SyntaxShape::String,
+                "application name sent to the server, it's shown in server logs",
+                None,
+            )
+            .switch(
+                "direct-connection",
+                "connect to the given host directly, instead of discovering the replica set",
+                None,
+            )
+            .named(
+                "compressors",
+                SyntaxShape::List(Box::new(SyntaxShape::String)),
+                "compressors of network traffic in preference order, can be zstd, snappy or zlib",
+                None,
+            )
+            .switch("tls", "connect with TLS", None)
+            .named(
+                "tls-ca-file",
+                SyntaxShape::Filepath,
+                "file contains certificate authorities to verify server certificate",
+                None,
+            )
+            .named(
+                "tls-cert-key-file",
+                SyntaxShape::Filepath,
+                "file contains client certificate and private key, also used by MONGODB-X509",
+                None,
+            )
+            .switch(
+                "tls-allow-invalid-certificates",
+                "don't verify server certificate, it's insecure",
+                None,
+            )
+            .switch(
+                "tls-allow-invalid-hostnames",
+                "don't verify hostname of server certificate, it's insecure",
+                None,
+            )
+            .named(
+                "uuid-representation",
+                SyntaxShape::String,
+                "how UUIDs are stored, can be standard, csharpLegacy, javaLegacy or pythonLegacy, legacy ones are binary subtype 3",
+                None,
+            )
+            .switch(
+                "persist",
+                "save the handle, so it's restored after plugin restarts",
+                None,
+            )
             .input_output_type(Type::Nothing, Type::Int)
-            .category(Category::Database)
+            .category(Category::Database);
+        with_write_flags(with_read_flags(sig))
     }
 
-    fn examples(&self) -> Vec<Example> {
-        vec![Example {
-            description: "Open a connection to mongodb",
-            example: "mongoc open \"mongodb://localhost/db\"",
-            result: None,
-        }]
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "Open a connection to mongodb",
+                example: "mongoc open \"mongodb://localhost/db\"",
+                result: None,
+            },
+            Example {
+                description: "Open a connection named `prod`, and query it by name",
+                example: "mongoc open \"mongodb://localhost/db\" -n prod; mongoc find -c students -d prod",
+                result: None,
+            },
+            Example {
+                description: "Open a connection which is restored after plugin restarts, the password is read from `$env.MONGO_PASSWORD`",
+                example: "mongoc open \"mongodb://alice@localhost/db\" --password-env MONGO_PASSWORD --persist",
+                result: None,
+            },
+            Example {
+                description: "Open a connection as `alice`, the password is read from terminal",
+                example: "mongoc open \"mongodb://localhost/db\" --user alice --password-prompt",
+                result: None,
+            },
+            Example {
+                description: "Open a connection to a single replica set member, and fail if it doesn't respond in 3 seconds",
+                example: "mongoc open \"mongodb://localhost:27018/db\" --direct-connection --connect-timeout 3sec",
+                result: None,
+            },
+            Example {
+                description: "Open a TLS connection, and verify server certificate with custom CA",
+                example: "mongoc open \"mongodb://localhost/db\" --tls --tls-ca-file ca.pem",
+                result: None,
+            },
+            Example {
+                description: "Open a connection with x.509 client certificate",
+                example: "mongoc open \"mongodb://localhost/db\" --tls --auth-mechanism MONGODB-X509 --tls-cert-key-file client.pem",
+                result: None,
+            },
+            Example {
+                description: "Open a connection with AWS IAM credentials from environment",
+                example: "mongoc open \"mongodb+srv://cluster0.example.mongodb.net/db\" --auth-mechanism MONGODB-AWS",
+                result: None,
+            },
+            Example {
+                description: "Open a connection to a database whose UUIDs are written by legacy java driver",
+                example: "mongoc open \"mongodb://localhost/db\" --uuid-representation javaLegacy",
+                result: None,
+            },
+            Example {
+                description: "Open a connection which reads from nearby secondaries, and waits writes to be acknowledged by majority",
+                example: "mongoc open \"mongodb://localhost/db\" --read-preference secondaryPreferred --read-tags [{dc: east}] --write-concern {w: majority}",
+                result: None,
+            },
+        ]
     }
 
     fn run(
@@ -37,9 +200,135 @@ impl SimplePluginCommand for Open {
         call: &nu_plugin::EvaluatedCall,
         _input: &Value,
     ) -> Result<Value, LabeledError> {
+        let bson_options = BsonOptions::from_engine(engine);
         let _ = engine.set_gc_disabled(true);
-        let conn_str: String = call.req(0)?;
-        let handler_id = plugin.connect(&conn_str)?;
+        let conn_str: Spanned<String> = call.req(0)?;
+        let persist = call.has_flag("persist")?;
+        let name: Option<Spanned<String>> = call.get_flag("name")?;
+        let password_env: Option<Spanned<String>> = call.get_flag("password-env")?;
+        let password = match &password_env {
+            None if call.has_flag("password-prompt")? => {
+                let _foreground = engine.enter_foreground()?;
+                Some(
+                    rpassword::prompt_password("password: ")
+                        .map_err(|e| LabeledError::new(format!("can't read password: {e}")))?,
+                )
+            }
+            None => None,
+            Some(var) => {
+                if call.has_flag("password-prompt")? {
+                    return Err(LabeledError::new("conflict password source")
+                        .with_label("can't be used with `--password-prompt`", var.span));
+                }
+                Some(
+                    engine
+                        .get_env_var(&var.item)?
+                        .ok_or_else(|| {
+                            LabeledError::new("environment variable not found")
+                                .with_label("not existed environment variable", var.span)
+                        })?
+                        .coerce_into_string()?,
+                )
+            }
+        };
+        if persist && password_env.is_none() {
+            if call.has_flag("password-prompt")? {
+                return Err(LabeledError::new("can't persist password")
+                    .with_label("password from terminal can't be restored", call.head)
+                    .with_help("Pass the password by `--password-env` instead"));
+            }
+            if strip_password(&conn_str.item).is_some() {
+                return Err(LabeledError::new("can't persist password")
+                    .with_label("connection url contains password", conn_str.span)
+                    .with_help("Remove password from the url, and pass it by `--password-env`"));
+            }
+        }
+        let auth_properties: Option<Record> = call.get_flag("auth-properties")?;
+        let tls_ca_file: Option<Spanned<String>> = call.get_flag("tls-ca-file")?;
+        let tls_cert_key_file: Option<Spanned<String>> = call.get_flag("tls-cert-key-file")?;
+        let max_pool_size: Option<Spanned<i64>> = call.get_flag("max-pool-size")?;
+        let max_pool_size = match max_pool_size {
+            None => None,
+            Some(size) => Some(u32::try_from(size.item).map_err(|_| {
+                LabeledError::new("get invalid number")
+                    .with_label(format!("should be between 0 and {}", u32::MAX), size.span)
+            })?),
+        };
+        let compressors: Option<Vec<Spanned<String>>> = call.get_flag("compressors")?;
+        if let Some(compressors) = &compressors {
+            for name in compressors {
+                if compressor(&name.item).is_none() {
+                    return Err(LabeledError::new("unknown compressor")
+                        .with_label("should be one of zstd, snappy, zlib", name.span));
+                }
+            }
+        }
+        let uuid_rep: Option<Spanned<String>> = call.get_flag("uuid-representation")?;
+        if let Some(rep) = &uuid_rep
+            && uuid_representation(&rep.item).is_none()
+        {
+            return Err(LabeledError::new("unknown UUID representation").with_label(
+                "should be one of standard, csharpLegacy, javaLegacy, pythonLegacy",
+                rep.span,
+            ));
+        }
+        let options = ConnectOptions {
+            read_preference: read_preference(call)?,
+            read_concern: read_concern(call)?,
+            write_concern: write_concern(call)?,
+            user: call.get_flag("user")?,
+            auth_source: call.get_flag("auth-source")?,
+            auth_mechanism: call.get_flag("auth-mechanism")?,
+            auth_properties: auth_properties
+                .map(|v| value_to_doc(v, &bson_options))
+                .transpose()?,
+            connect_timeout: duration_flag(call, "connect-timeout")?,
+            server_selection_timeout: duration_flag(call, "server-selection-timeout")?,
+            max_pool_size,
+            app_name: call.get_flag("app-name")?,
+            direct_connection: call.has_flag("direct-connection")?.then_some(true),
+            compressors: compressors.map(|c| c.into_iter().map(|name| name.item).collect()),
+            tls: call.has_flag("tls")?.then_some(true),
+            tls_ca_file: tls_ca_file
+                .map(|path| readable_file(engine, path))
+                .transpose()?,
+            tls_cert_key_file: tls_cert_key_file
+                .map(|path| readable_file(engine, path))
+                .transpose()?,
+            tls_allow_invalid_certificates: call
+                .has_flag("tls-allow-invalid-certificates")?
+                .then_some(true),
+            tls_allow_invalid_hostnames: call
+                .has_flag("tls-allow-invalid-hostnames")?
+                .then_some(true),
+            uuid_representation: uuid_rep.map(|rep| rep.item),
+        };
+        let handler_id = plugin.connect(&conn_str, password, options, name)?;
+        if persist {
+            plugin.persist_handle(handler_id, password_env.map(|v| v.item), call.head)?;
+        }
         Ok(Value::int(handler_id.into(), call.head))
     }
 }
+
+/// Get absolute path of the file, and make sure it's readable.
+fn readable_file(engine: &EngineInterface, path: Spanned<String>) -> Result<PathBuf, LabeledError> {
+    let full_path = PathBuf::from(engine.get_current_dir()?).join(&path.item);
+    std::fs::File::open(&full_path).map_err(|e| {
+        LabeledError::new(format!("can't read {}", full_path.display()))
+            .with_label(format!("{e}"), path.span)
+    })?;
+    Ok(full_path)
+}
+
+fn duration_flag(call: &EvaluatedCall, name: &str) -> Result<Option<Duration>, LabeledError> {
+    let duration: Option<Spanned<i64>> = call.get_flag(name)?;
+    match duration {
+        None => Ok(None),
+        Some(d) => u64::try_from(d.item)
+            .map(|nanos| Some(Duration::from_nanos(nanos)))
+            .map_err(|_| {
+                LabeledError::new("get invalid duration").with_label("can't be negative", d.span)
+            }),
+    }
+}
@@ -0,0 +1,91 @@
+use crate::MongoPlugin;
+use crate::error::mongo_error;
+use mongodb::bson::doc;
+use mongodb::sync::Database;
+use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Signature, SyntaxShape, Type, Value,
+    engine::ArgType,
+};
+use std::time::{Duration, Instant};
+
+pub struct Ping;
+
+impl SimplePluginCommand for Ping {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc ping"
+    }
+
+    fn description(&self) -> &str {
+        "ping mongodb server of a handle, returns round-trip latency"
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc ping")
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .input_output_type(Type::Nothing, Type::Duration)
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "ping server of current database handle",
+                example: "mongoc ping",
+                result: None,
+            },
+            Example {
+                description: "ping server of database handle 1",
+                example: "mongoc ping -d 1",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let db = match db_handle {
+            None => plugin.get_handle(plugin.get_current()?, call.head)?,
+            Some(db_handle) => {
+                plugin.get_handle(plugin.resolve_handle_id(&db_handle)?, db_handle.span())?
+            }
+        };
+        let latency = ping_db(&db).map_err(|e| mongo_error(e, call))?;
+        Ok(Value::duration(latency.as_nanos() as i64, call.head))
+    }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        _engine: &EngineInterface,
+        _call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
+
+/// Run `ping` command against the database, returns round-trip latency.
+pub(crate) fn ping_db(db: &Database) -> Result<Duration, mongodb::error::Error> {
+    let start = Instant::now();
+    db.run_command(doc! {"ping": 1}).run()?;
+    Ok(start.elapsed())
+}
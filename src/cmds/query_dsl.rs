@@ -0,0 +1,757 @@
+//! Compile `where`-style conditions into mongodb filters, e.g: `{|r| $r.age > 18 and $r.name =~ '^J'}`
+//! gives `{$and: [{age: {$gt: 18}}, {name: {$regex: '^J'}}]}`.
+//!
+//! Closures are not evaluated, their source code is parsed, so only comparisons of fields with
+//! literals are supported.
+use super::val_converter::BsonOptions;
+use mongodb::bson::{Bson, Document, Regex, doc};
+use nu_plugin::{EngineInterface, EvaluatedCall};
+use nu_protocol::{LabeledError, Signature, Span, SyntaxShape, Value};
+
+pub(super) fn with_where_flag(sig: Signature) -> Signature {
+    sig.named(
+        "where",
+        SyntaxShape::OneOf(vec![SyntaxShape::Closure(None), SyntaxShape::String]),
+        "condition of documents, e.g: {|r| $r.age > 18 and $r.name =~ '^J'} or \"age > 18\"",
+        Some('w'),
+    )
+}
+
+/// Get filter from query positional and `--where` flag, they're combined by `$and`.
+pub(super) fn query_filter(
+    engine: &EngineInterface,
+    call: &EvaluatedCall,
+    query: Document,
+    bson_options: &BsonOptions,
+) -> Result<Document, LabeledError> {
+    let Some(condition) = where_filter(engine, call, bson_options.clone())? else {
+        return Ok(query);
+    };
+    if query.is_empty() {
+        Ok(condition)
+    } else {
+        Ok(doc! { "$and": [query, condition] })
+    }
+}
+
+fn where_filter(
+    engine: &EngineInterface,
+    call: &EvaluatedCall,
+    bson_options: BsonOptions,
+) -> Result<Option<Document>, LabeledError> {
+    let Some(value) = call.get_flag_value("where") else {
+        return Ok(None);
+    };
+    let span = value.span();
+    match value {
+        Value::Closure { .. } => {
+            let source = engine.get_span_contents(span)?;
+            let source = String::from_utf8_lossy(&source);
+            let (body, offset, param) = closure_body(&source, span)?;
+            let tokens = lex(body, Some(span.start + offset), span)?;
+            Parser::new(tokens, param, Some(span.start + offset), span, bson_options)
+                .parse()
+                .map(Some)
+        }
+        Value::String { val, .. } => {
+            let tokens = lex(&val, None, span)?;
+            Parser::new(tokens, None, None, span, bson_options)
+                .parse()
+                .map(Some)
+        }
+        other => Err(LabeledError::new("invalid condition").with_label(
+            format!("expected closure or string, got {}", other.get_type()),
+            span,
+        )),
+    }
+}
+
+/// Strip braces and parameters of the closure, returns body, its offset and the parameter name.
+fn closure_body(source: &str, span: Span) -> Result<(&str, usize, Option<String>), LabeledError> {
+    let invalid = || LabeledError::new("invalid closure").with_label("can't parse closure", span);
+    let inner = source
+        .trim_end()
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .ok_or_else(invalid)?;
+    let trimmed = inner.trim_start();
+    let mut offset = 1 + inner.len() - trimmed.len();
+    let Some(params) = trimmed.strip_prefix('|') else {
+        return Ok((trimmed, offset, None));
+    };
+    let end = params.find('|').ok_or_else(invalid)?;
+    let param = params[..end]
+        .split(',')
+        .next()
+        .map(|p| p.split(':').next().unwrap_or(p).trim().to_string())
+        .filter(|p| !p.is_empty());
+    offset += end + 2;
+    Ok((&params[end + 1..], offset, param))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Var(String),
+    Word(String),
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Op(&'static str),
+    Dot,
+    Question,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+}
+
+struct Token {
+    tok: Tok,
+    start: usize,
+    end: usize,
+}
+
+fn to_span(base: Option<usize>, span: Span, start: usize, end: usize) -> Span {
+    match base {
+        Some(base) => Span::new(base + start, base + end),
+        None => span,
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '+')
+}
+
+fn lex(source: &str, base: Option<usize>, span: Span) -> Result<Vec<Token>, LabeledError> {
+    let mut tokens: Vec<Token> = vec![];
+    let chars: Vec<(usize, char)> = source.char_indices().collect();
+    let byte_at = |i: usize| chars.get(i).map(|(b, _)| *b).unwrap_or(source.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        let after_dot = matches!(tokens.last(), Some(Token { tok: Tok::Dot, .. }));
+        let (tok, next) = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => (Tok::LParen, i + 1),
+            ')' => (Tok::RParen, i + 1),
+            '[' => (Tok::LBracket, i + 1),
+            ']' => (Tok::RBracket, i + 1),
+            ',' => (Tok::Comma, i + 1),
+            '.' => (Tok::Dot, i + 1),
+            '?' => (Tok::Question, i + 1),
+            '=' | '!' | '<' | '>' => {
+                let second = chars.get(i + 1).map(|(_, c)| *c);
+                match (c, second) {
+                    ('=', Some('=')) => (Tok::Op("=="), i + 2),
+                    ('=', Some('~')) => (Tok::Op("=~"), i + 2),
+                    ('!', Some('=')) => (Tok::Op("!="), i + 2),
+                    ('!', Some('~')) => (Tok::Op("!~"), i + 2),
+                    ('<', Some('=')) => (Tok::Op("<="), i + 2),
+                    ('>', Some('=')) => (Tok::Op(">="), i + 2),
+                    ('<', _) => (Tok::Op("<"), i + 1),
+                    ('>', _) => (Tok::Op(">"), i + 1),
+                    _ => {
+                        return Err(LabeledError::new("unsupported operator").with_label(
+                            "unknown operator",
+                            to_span(base, span, start, start + 1),
+                        ));
+                    }
+                }
+            }
+            '"' | '\'' | '`' => {
+                let mut val = String::new();
+                let mut j = i + 1;
+                loop {
+                    let Some((_, ch)) = chars.get(j) else {
+                        return Err(LabeledError::new("unclosed string").with_label(
+                            "string is not closed",
+                            to_span(base, span, start, source.len()),
+                        ));
+                    };
+                    j += 1;
+                    if *ch == c {
+                        break;
+                    }
+                    if c == '"' && *ch == '\\' {
+                        let escaped = chars.get(j).map(|(_, e)| *e).unwrap_or('\\');
+                        j += 1;
+                        val.push(match escaped {
+                            'n' => '\n',
+                            't' => '\t',
+                            'r' => '\r',
+                            other => other,
+                        });
+                    } else {
+                        val.push(*ch);
+                    }
+                }
+                (Tok::Str(val), j)
+            }
+            '$' => {
+                let mut j = i + 1;
+                while j < chars.len() && is_word_char(chars[j].1) {
+                    j += 1;
+                }
+                (Tok::Var(source[byte_at(i + 1)..byte_at(j)].to_string()), j)
+            }
+            c if is_word_char(c) => {
+                let mut j = i;
+                // `.` is part of float literals, but it separates members after `$r`.
+                let numeric = !after_dot
+                    && (c.is_ascii_digit()
+                        || (c == '-' && chars.get(i + 1).is_some_and(|(_, d)| d.is_ascii_digit())));
+                while j < chars.len()
+                    && (is_word_char(chars[j].1) || (numeric && chars[j].1 == '.'))
+                {
+                    j += 1;
+                }
+                let word = &source[start..byte_at(j)];
+                let tok = if after_dot {
+                    Tok::Word(word.to_string())
+                } else if let Ok(i) = word.replace('_', "").parse::<i64>() {
+                    Tok::Int(i)
+                } else if let Ok(f) = word.parse::<f64>() {
+                    Tok::Float(f)
+                } else {
+                    Tok::Word(word.to_string())
+                };
+                (tok, j)
+            }
+            _ => {
+                return Err(LabeledError::new("unsupported syntax").with_label(
+                    format!("unexpected `{c}`"),
+                    to_span(base, span, start, start + c.len_utf8()),
+                ));
+            }
+        };
+        tokens.push(Token {
+            tok,
+            start,
+            end: byte_at(next),
+        });
+        i = next;
+    }
+    Ok(tokens)
+}
+
+enum Operand {
+    Field(String),
+    Literal(Bson),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// closure parameter, its members are document fields.
+    param: Option<String>,
+    base: Option<usize>,
+    span: Span,
+    bson_options: BsonOptions,
+}
+
+impl Parser {
+    fn new(
+        tokens: Vec<Token>,
+        param: Option<String>,
+        base: Option<usize>,
+        span: Span,
+        bson_options: BsonOptions,
+    ) -> Self {
+        Self {
+            tokens,
+            pos: 0,
+            param,
+            base,
+            span,
+            bson_options,
+        }
+    }
+
+    fn parse(mut self) -> Result<Document, LabeledError> {
+        if self.tokens.is_empty() {
+            return Err(
+                LabeledError::new("empty condition").with_label("condition is required", self.span)
+            );
+        }
+        let filter = self.parse_or()?;
+        if let Some(token) = self.tokens.get(self.pos) {
+            return Err(self.error_at(token, "unexpected token"));
+        }
+        Ok(filter)
+    }
+
+    fn error_at(&self, token: &Token, label: &str) -> LabeledError {
+        LabeledError::new("can't compile condition to mongodb query")
+            .with_label(label, to_span(self.base, self.span, token.start, token.end))
+    }
+
+    fn error_at_end(&self, label: &str) -> LabeledError {
+        match self.tokens.last() {
+            Some(token) => self.error_at(token, label),
+            None => LabeledError::new("can't compile condition to mongodb query")
+                .with_label(label, self.span),
+        }
+    }
+
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|t| &t.tok)
+    }
+
+    fn peek_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Word(w)) if w == word)
+    }
+
+    fn parse_or(&mut self) -> Result<Document, LabeledError> {
+        let mut branches = vec![self.parse_and()?];
+        while self.peek_word("or") {
+            self.pos += 1;
+            branches.push(self.parse_and()?);
+        }
+        Ok(combine("$or", branches))
+    }
+
+    fn parse_and(&mut self) -> Result<Document, LabeledError> {
+        let mut branches = vec![self.parse_not()?];
+        while self.peek_word("and") {
+            self.pos += 1;
+            branches.push(self.parse_not()?);
+        }
+        Ok(combine("$and", branches))
+    }
+
+    fn parse_not(&mut self) -> Result<Document, LabeledError> {
+        if self.peek_word("not") {
+            self.pos += 1;
+            let inner = self.parse_not()?;
+            return Ok(doc! { "$nor": [inner] });
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Document, LabeledError> {
+        if self.peek() == Some(&Tok::LParen) {
+            self.pos += 1;
+            let inner = self.parse_or()?;
+            if self.peek() != Some(&Tok::RParen) {
+                return Err(self.error_at_end("expected `)`"));
+            }
+            self.pos += 1;
+            return Ok(inner);
+        }
+        let lhs_pos = self.pos;
+        let lhs = self.parse_operand(true)?;
+        let op = match self.peek() {
+            Some(Tok::Op(op)) => *op,
+            Some(Tok::Word(w)) => match w.as_str() {
+                "in" => "in",
+                "not-in" => "not-in",
+                "has" => "has",
+                "not-has" => "not-has",
+                "like" => "=~",
+                "not-like" => "!~",
+                "starts-with" => "starts-with",
+                "ends-with" => "ends-with",
+                _ => return self.single_operand(lhs, lhs_pos),
+            },
+            _ => return self.single_operand(lhs, lhs_pos),
+        };
+        let op_pos = self.pos;
+        self.pos += 1;
+        let rhs = self.parse_operand(false)?;
+        let op_token = &self.tokens[op_pos];
+        match (lhs, rhs) {
+            (Operand::Field(field), Operand::Literal(value)) => {
+                self.compare(&field, op, value, op_token)
+            }
+            (Operand::Literal(value), Operand::Field(field)) => {
+                // swap sides, e.g: `18 < $r.age` is `$r.age > 18`.
+                let flipped = match op {
+                    "<" => ">",
+                    ">" => "<",
+                    "<=" => ">=",
+                    ">=" => "<=",
+                    "==" | "!=" => op,
+                    "in" => "has",
+                    "not-in" => "not-has",
+                    _ => {
+                        return Err(
+                            self.error_at(op_token, "field should be at the left side of operator")
+                        );
+                    }
+                };
+                self.compare(&field, flipped, value, op_token)
+            }
+            (Operand::Field(_), Operand::Field(_)) => {
+                Err(self.error_at(op_token, "comparing two fields is not supported"))
+            }
+            (Operand::Literal(_), Operand::Literal(_)) => {
+                Err(self.error_at(op_token, "one side of operator should be a field"))
+            }
+        }
+    }
+
+    /// A field alone is a boolean check, e.g: `$r.active`.
+    fn single_operand(&self, operand: Operand, pos: usize) -> Result<Document, LabeledError> {
+        match operand {
+            Operand::Field(field) => Ok(doc! { field: true }),
+            Operand::Literal(_) => Err(self.error_at(&self.tokens[pos], "expected a comparison")),
+        }
+    }
+
+    fn compare(
+        &self,
+        field: &str,
+        op: &str,
+        value: Bson,
+        op_token: &Token,
+    ) -> Result<Document, LabeledError> {
+        let regex = |pattern: String| {
+            Bson::RegularExpression(Regex {
+                pattern,
+                options: String::new(),
+            })
+        };
+        let value = self.int_width(field, value);
+        let condition = match op {
+            "==" | "has" => value,
+            "!=" | "not-has" => Bson::Document(doc! { "$ne": value }),
+            "<" => Bson::Document(doc! { "$lt": value }),
+            "<=" => Bson::Document(doc! { "$lte": value }),
+            ">" => Bson::Document(doc! { "$gt": value }),
+            ">=" => Bson::Document(doc! { "$gte": value }),
+            "in" | "not-in" => {
+                if !matches!(value, Bson::Array(_)) {
+                    return Err(self.error_at(op_token, "right side should be a list"));
+                }
+                let key = if op == "in" { "$in" } else { "$nin" };
+                Bson::Document(doc! { key: value })
+            }
+            "=~" | "!~" | "starts-with" | "ends-with" => {
+                let Bson::String(s) = value else {
+                    return Err(self.error_at(op_token, "right side should be a string"));
+                };
+                match op {
+                    "=~" => regex(s),
+                    "!~" => Bson::Document(doc! { "$not": regex(s) }),
+                    "starts-with" => regex(format!("^{}", escape_regex(&s))),
+                    _ => regex(format!("{}$", escape_regex(&s))),
+                }
+            }
+            _ => return Err(self.error_at(op_token, "unsupported operator")),
+        };
+        Ok(doc! { field: condition })
+    }
+
+    /// Convert integer literals by `int_fields` of the field, or `integer_mode`.
+    fn int_width(&self, field: &str, value: Bson) -> Bson {
+        match value {
+            Bson::Int64(i) => self.bson_options.int_at(field, i),
+            Bson::Array(vals) => {
+                Bson::Array(vals.into_iter().map(|v| self.int_width(field, v)).collect())
+            }
+            other => other,
+        }
+    }
+
+    /// Parse a field or a literal, bare words are fields at the left side, and strings at the right side.
+    fn parse_operand(&mut self, left: bool) -> Result<Operand, LabeledError> {
+        let Some(token) = self.tokens.get(self.pos) else {
+            return Err(self.error_at_end("expected a field or a value"));
+        };
+        let operand = match &token.tok {
+            Tok::Var(name) => {
+                let is_row =
+                    self.param.as_deref() == Some(name.as_str()) || name == "in" || name == "it";
+                if !is_row {
+                    return Err(self
+                        .error_at(
+                            token,
+                            "variables are not supported, only fields of the row can be used",
+                        )
+                        .with_help("Put the value into the condition directly"));
+                }
+                if self.tokens.get(self.pos + 1).map(|t| &t.tok) != Some(&Tok::Dot) {
+                    return Err(self.error_at(token, "expected a field of the row, e.g: `$r.name`"));
+                }
+                self.pos += 1;
+                return self.parse_members(String::new()).map(Operand::Field);
+            }
+            Tok::Word(word) => match word.as_str() {
+                "true" => Operand::Literal(Bson::Boolean(true)),
+                "false" => Operand::Literal(Bson::Boolean(false)),
+                "null" => Operand::Literal(Bson::Null),
+                word if left => {
+                    let word = word.to_string();
+                    self.pos += 1;
+                    if self.peek() == Some(&Tok::Dot) {
+                        return self.parse_members(word).map(Operand::Field);
+                    }
+                    return Ok(Operand::Field(word));
+                }
+                word => Operand::Literal(word_literal(word)),
+            },
+            Tok::Str(s) => Operand::Literal(Bson::String(s.clone())),
+            // width of integers is decided by the field, see `compare`.
+            Tok::Int(i) => Operand::Literal(Bson::Int64(*i)),
+            Tok::Float(f) => Operand::Literal(Bson::Double(*f)),
+            Tok::LBracket => {
+                self.pos += 1;
+                return self.parse_list().map(Operand::Literal);
+            }
+            _ => return Err(self.error_at(token, "expected a field or a value")),
+        };
+        self.pos += 1;
+        Ok(operand)
+    }
+
+    /// Parse `.a.b` after the row variable or a bare word.
+    fn parse_members(&mut self, mut path: String) -> Result<String, LabeledError> {
+        while self.peek() == Some(&Tok::Dot) {
+            self.pos += 1;
+            let Some(token) = self.tokens.get(self.pos) else {
+                return Err(self.error_at_end("expected a field name"));
+            };
+            let member = match &token.tok {
+                Tok::Word(w) | Tok::Str(w) => w.clone(),
+                Tok::Int(i) => i.to_string(),
+                _ => return Err(self.error_at(token, "expected a field name")),
+            };
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(&member);
+            self.pos += 1;
+            if self.peek() == Some(&Tok::Question) {
+                self.pos += 1;
+            }
+        }
+        Ok(path)
+    }
+
+    fn parse_list(&mut self) -> Result<Bson, LabeledError> {
+        let mut items = vec![];
+        loop {
+            match self.peek() {
+                None => return Err(self.error_at_end("expected `]`")),
+                Some(Tok::RBracket) => {
+                    self.pos += 1;
+                    return Ok(Bson::Array(items));
+                }
+                Some(Tok::Comma) => self.pos += 1,
+                _ => match self.parse_operand(false)? {
+                    Operand::Literal(item) => items.push(item),
+                    Operand::Field(_) => {
+                        return Err(self.error_at(&self.tokens[self.pos - 1], "expected a value"));
+                    }
+                },
+            }
+        }
+    }
+}
+
+fn combine(op: &str, mut branches: Vec<Document>) -> Document {
+    if branches.len() == 1 {
+        branches.remove(0)
+    } else {
+        doc! { op: branches }
+    }
+}
+
+/// Bare words at the right side are strings, or dates if they look like dates, e.g: `2024-01-31`.
+fn word_literal(word: &str) -> Bson {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(word) {
+        return Bson::DateTime(bson::DateTime::from_chrono(dt));
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        let dt = date.and_time(chrono::NaiveTime::MIN).and_utc();
+        return Bson::DateTime(bson::DateTime::from_chrono(dt));
+    }
+    Bson::String(word.to_string())
+}
+
+fn escape_regex(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmds::val_converter::IntegerMode;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    /// Span of the closure in nushell source, tokens are reported relative to it.
+    const BASE: usize = 100;
+
+    fn compile_with(source: &str, bson_options: BsonOptions) -> Result<Document, LabeledError> {
+        let span = Span::new(BASE, BASE + source.len());
+        let (body, offset, param) = closure_body(source, span)?;
+        let tokens = lex(body, Some(BASE + offset), span)?;
+        Parser::new(tokens, param, Some(BASE + offset), span, bson_options).parse()
+    }
+
+    fn compile(source: &str) -> Result<Document, LabeledError> {
+        compile_with(source, BsonOptions::default())
+    }
+
+    /// Span of `needle` in the closure source.
+    fn span_of(source: &str, needle: &str) -> Span {
+        let start = BASE + source.find(needle).expect("needle should be in source");
+        Span::new(start, start + needle.len())
+    }
+
+    fn assert_error_at(source: &str, needle: &str, label: &str) {
+        let err = compile(source).expect_err("condition should be rejected");
+        assert_eq!(err.labels[0].text, label);
+        assert_eq!(err.labels[0].span, span_of(source, needle));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            compile("{|r| $r.a == 1 or $r.b == 2 and $r.c == 3}").unwrap(),
+            doc! { "$or": [{ "a": 1 }, { "$and": [{ "b": 2 }, { "c": 3 }] }] }
+        );
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        assert_eq!(
+            compile("{|r| ($r.a == 1 or $r.b == 2) and $r.c == 3}").unwrap(),
+            doc! { "$and": [{ "$or": [{ "a": 1 }, { "b": 2 }] }, { "c": 3 }] }
+        );
+    }
+
+    #[test]
+    fn not_is_nor() {
+        assert_eq!(
+            compile("{|r| not $r.a == 1 and $r.b == 2}").unwrap(),
+            doc! { "$and": [{ "$nor": [{ "a": 1 }] }, { "b": 2 }] }
+        );
+        assert_eq!(
+            compile("{|r| not ($r.a == 1 or $r.b == 2)}").unwrap(),
+            doc! { "$nor": [{ "$or": [{ "a": 1 }, { "b": 2 }] }] }
+        );
+    }
+
+    #[test]
+    fn literal_at_left_side_is_flipped() {
+        assert_eq!(
+            compile("{|r| 18 < $r.age}").unwrap(),
+            doc! { "age": { "$gt": 18 } }
+        );
+        assert_eq!(
+            compile("{|r| 'admin' in $r.roles}").unwrap(),
+            doc! { "roles": "admin" }
+        );
+    }
+
+    #[test]
+    fn membership_operators() {
+        assert_eq!(
+            compile("{|r| $r.tag in [a, 'b c'] and $r.n not-in [1, 2]}").unwrap(),
+            doc! { "$and": [{ "tag": { "$in": ["a", "b c"] } }, { "n": { "$nin": [1, 2] } }] }
+        );
+        assert_eq!(
+            compile("{|r| $r.roles not-has guest}").unwrap(),
+            doc! { "roles": { "$ne": "guest" } }
+        );
+    }
+
+    #[test]
+    fn string_conditions_use_bare_fields() {
+        let span = Span::new(0, 0);
+        let tokens = lex("address.city == Paris", None, span).unwrap();
+        assert_eq!(
+            Parser::new(tokens, None, None, span, BsonOptions::default())
+                .parse()
+                .unwrap(),
+            doc! { "address.city": "Paris" }
+        );
+    }
+
+    #[test]
+    fn starts_with_and_ends_with_escape_regex() {
+        let regex = |pattern: &str| {
+            Bson::RegularExpression(Regex {
+                pattern: pattern.to_string(),
+                options: String::new(),
+            })
+        };
+        assert_eq!(
+            compile("{|r| $r.name starts-with 'a.b*'}").unwrap(),
+            doc! { "name": regex(r"^a\.b\*") }
+        );
+        assert_eq!(
+            compile("{|r| $r.file ends-with '(1).tar.gz'}").unwrap(),
+            doc! { "file": regex(r"\(1\)\.tar\.gz$") }
+        );
+        // patterns of `=~` are kept as they are.
+        assert_eq!(
+            compile("{|r| $r.name =~ '^J.*'}").unwrap(),
+            doc! { "name": regex("^J.*") }
+        );
+    }
+
+    #[test]
+    fn integers_follow_int_fields() {
+        let bson_options = BsonOptions {
+            int_fields: Arc::new(HashMap::from([("views".to_string(), IntegerMode::Int64)])),
+            ..BsonOptions::default()
+        };
+        let filter =
+            compile_with("{|r| $r.views > 10 and $r.age in [1, 2]}", bson_options).unwrap();
+        assert_eq!(
+            filter,
+            doc! { "$and": [
+                { "views": { "$gt": Bson::Int64(10) } },
+                { "age": { "$in": [Bson::Int32(1), Bson::Int32(2)] } },
+            ] }
+        );
+    }
+
+    #[test]
+    fn unsupported_expressions_point_at_the_token() {
+        assert_error_at(
+            "{|r| $r.age > $min}",
+            "$min",
+            "variables are not supported, only fields of the row can be used",
+        );
+        assert_error_at(
+            "{|r| $r.a == $r.b}",
+            "==",
+            "comparing two fields is not supported",
+        );
+        assert_error_at(
+            "{|r| 1 == 2}",
+            "==",
+            "one side of operator should be a field",
+        );
+        assert_error_at("{|r| $r.a = 1}", "=", "unknown operator");
+        assert_error_at("{|r| $r.a == 1 xor $r.b == 2}", "xor", "unexpected token");
+        assert_error_at("{|r| $r.tags in 'a'}", "in", "right side should be a list");
+    }
+
+    #[test]
+    fn unclosed_string_points_to_the_end() {
+        let source = "{|r| $r.name == 'abc}";
+        let err = compile(source).expect_err("condition should be rejected");
+        assert_eq!(err.labels[0].text, "string is not closed");
+        let start = BASE + source.find('\'').unwrap();
+        assert_eq!(
+            err.labels[0].span,
+            Span::new(start, BASE + source.len() - 1)
+        );
+    }
+}
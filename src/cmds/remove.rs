@@ -1,8 +1,8 @@
 use crate::MongoPlugin;
 use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
 use nu_protocol::{
-    Category, DynamicSuggestion, Example, LabeledError, Signature, Spanned, SyntaxShape, Type,
-    Value, engine::ArgType,
+    Category, DynamicSuggestion, Example, LabeledError, Signature, SyntaxShape, Type, Value,
+    engine::ArgType,
 };
 
 pub struct Remove;
@@ -18,18 +18,22 @@ impl SimplePluginCommand for Remove {
         "remove mongodb handles"
     }
 
+    fn extra_description(&self) -> &str {
+        "Persisted handle is also removed from session file."
+    }
+
     fn signature(&self) -> nu_protocol::Signature {
         Signature::build("mongoc remove")
             .required(
                 "db-handle",
-                SyntaxShape::Int,
-                "database handle to remove, can get from `mongoc list`",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name to remove, can get from `mongoc list`",
             )
             .input_output_type(Type::Nothing, Type::Nothing)
             .category(Category::Database)
     }
 
-    fn examples(&self) -> Vec<Example> {
+    fn examples(&self) -> Vec<Example<'_>> {
         vec![Example {
             description: "Remove handle 0",
             example: "mongoc remove 0",
@@ -44,10 +48,11 @@ impl SimplePluginCommand for Remove {
         call: &nu_plugin::EvaluatedCall,
         _input: &Value,
     ) -> Result<Value, LabeledError> {
-        let handle_id: Spanned<i64> = call.req(0)?;
-        plugin.remove_handle(handle_id.item as u8, handle_id.span)?;
+        let handle_id: Value = call.req(0)?;
+        plugin.remove_handle(plugin.resolve_handle_id(&handle_id)?, handle_id.span())?;
         Ok(Value::nothing(call.head))
     }
+    #[allow(deprecated)]
     fn get_dynamic_completion(
         &self,
         plugin: &Self::Plugin,
@@ -57,9 +62,7 @@ impl SimplePluginCommand for Remove {
         _experimental: nu_protocol::engine::ExperimentalMarker,
     ) -> Option<Vec<DynamicSuggestion>> {
         match arg_type {
-            ArgType::Flag(name) if name == "collection" => {
-                super::get_collection_names_at_current_handle(plugin)
-            }
+            ArgType::Positional(0) => super::get_handle_ids(plugin),
             _ => None,
         }
     }
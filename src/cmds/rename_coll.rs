@@ -0,0 +1,123 @@
+use super::concern::{with_write_flags, write_concern};
+use super::val_converter::{BsonOptions, doc_to_value};
+use crate::MongoPlugin;
+use crate::error::mongo_error;
+use mongodb::bson::{doc, to_bson};
+use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Signature, SyntaxShape, Type, Value,
+    engine::ArgType,
+};
+
+pub struct RenameColl;
+
+impl SimplePluginCommand for RenameColl {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc rename-coll"
+    }
+
+    fn description(&self) -> &str {
+        "rename a mongodb collection"
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        let sig = Signature::build("mongoc rename-coll")
+            .required("from", SyntaxShape::String, "collection to rename")
+            .required("to", SyntaxShape::String, "new collection name")
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .named(
+                "to-db",
+                SyntaxShape::String,
+                "move the collection to another database, default is the database of handle",
+                None,
+            )
+            .switch(
+                "drop-target",
+                "drop the target collection if it exists",
+                None,
+            )
+            .input_output_type(Type::Nothing, Type::record())
+            .category(Category::Database);
+        with_write_flags(sig)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "rename `students` collection to `pupils`",
+                example: "mongoc rename-coll students pupils",
+                result: None,
+            },
+            Example {
+                description: "move `students` collection to `archive` database, and replace existing one",
+                example: "mongoc rename-coll students students --to-db archive --drop-target",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, span) = match db_handle {
+            None => (plugin.get_current()?, call.head),
+            Some(db_handle) => (plugin.resolve_handle_id(&db_handle)?, db_handle.span()),
+        };
+        let db = plugin.get_handle(handle_id, span)?;
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
+        let from: String = call.req(0)?;
+        let to: String = call.req(1)?;
+        let to_db: String = call
+            .get_flag("to-db")?
+            .unwrap_or_else(|| db.name().to_string());
+
+        let mut command = doc! {
+            "renameCollection": format!("{}.{from}", db.name()),
+            "to": format!("{to_db}.{to}"),
+            "dropTarget": call.has_flag("drop-target")?,
+        };
+        if let Some(wc) = write_concern(call)? {
+            command.insert(
+                "writeConcern",
+                to_bson(&wc).map_err(|e| LabeledError::new(format!("{e}")))?,
+            );
+        }
+        // `renameCollection` can only run against admin database.
+        let reply = plugin
+            .get_client(handle_id, span)?
+            .database("admin")
+            .run_command(command)
+            .run()
+            .map_err(|e| mongo_error(e, call))?;
+        plugin.invalidate_completions(handle_id, None);
+
+        Ok(doc_to_value(reply, call.head, &bson_options))
+    }
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Positional(0) => super::get_collection_names_at_handle(plugin, engine, &call),
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use super::concern::{collection_options, with_read_flags};
+use super::val_converter::{BsonOptions, bson_to_value, value_to_doc};
+use crate::MongoPlugin;
+use crate::error::mongo_error;
+use mongodb::bson::{Bson, DateTime, Document, doc};
+use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
+use nu_protocol::{
+    Category, DynamicSuggestion, Example, LabeledError, Record, Signature, Span, Spanned,
+    SyntaxShape, Type, Value, engine::ArgType, record,
+};
+use std::collections::{BTreeMap, HashSet};
+
+const MAX_EXAMPLES: usize = 3;
+
+pub struct Schema;
+
+/// Observed information of a field path.
+#[derive(Default)]
+struct FieldStats {
+    /// How many sampled documents contain the field.
+    presence: u64,
+    /// How many values are observed, values inside arrays are counted one by one.
+    occurrences: u64,
+    types: BTreeMap<&'static str, u64>,
+    examples: Vec<Bson>,
+    min_num: Option<f64>,
+    max_num: Option<f64>,
+    min_date: Option<DateTime>,
+    max_date: Option<DateTime>,
+}
+
+impl FieldStats {
+    fn observe(&mut self, val: &Bson) {
+        self.occurrences += 1;
+        *self.types.entry(type_name(val)).or_default() += 1;
+        let num = match val {
+            Bson::Int32(i) => Some(f64::from(*i)),
+            Bson::Int64(i) => Some(*i as f64),
+            Bson::Double(f) if !f.is_nan() => Some(*f),
+            _ => None,
+        };
+        if let Some(num) = num {
+            self.min_num = Some(self.min_num.map_or(num, |m| m.min(num)));
+            self.max_num = Some(self.max_num.map_or(num, |m| m.max(num)));
+        }
+        if let Bson::DateTime(dt) = val {
+            self.min_date = Some(self.min_date.map_or(*dt, |m| m.min(*dt)));
+            self.max_date = Some(self.max_date.map_or(*dt, |m| m.max(*dt)));
+        }
+        if !matches!(val, Bson::Document(_) | Bson::Array(_))
+            && self.examples.len() < MAX_EXAMPLES
+            && !self.examples.contains(val)
+        {
+            self.examples.push(val.clone())
+        }
+    }
+
+    fn into_value(
+        self,
+        path: String,
+        sampled: u64,
+        span: Span,
+        bson_options: &BsonOptions,
+    ) -> Value {
+        let mut types = Record::new();
+        for (name, count) in self.types {
+            types.push(
+                name,
+                Value::float(count as f64 * 100.0 / self.occurrences as f64, span),
+            );
+        }
+        let (min, max) = match (self.min_num, self.max_num, self.min_date, self.max_date) {
+            (Some(min), Some(max), _, _) => (Value::float(min, span), Value::float(max, span)),
+            (_, _, Some(min), Some(max)) => (
+                Value::date(min.to_chrono().into(), span),
+                Value::date(max.to_chrono().into(), span),
+            ),
+            _ => (Value::nothing(span), Value::nothing(span)),
+        };
+        Value::record(
+            record! {
+                "path" => Value::string(path, span),
+                "types" => Value::record(types, span),
+                "presence" => Value::float(self.presence as f64 / sampled as f64, span),
+                "examples" => Value::list(
+                    self.examples.into_iter().map(|e| bson_to_value(e, span, bson_options)).collect(),
+                    span,
+                ),
+                "min" => min,
+                "max" => max,
+            },
+            span,
+        )
+    }
+}
+
+/// Name of bson type, it's the same to mongodb `$type` alias.
+fn type_name(val: &Bson) -> &'static str {
+    match val {
+        Bson::Double(_) => "double",
+        Bson::String(_) => "string",
+        Bson::Document(_) => "object",
+        Bson::Array(_) => "array",
+        Bson::Binary(_) => "binData",
+        Bson::Undefined => "undefined",
+        Bson::ObjectId(_) => "objectId",
+        Bson::Boolean(_) => "bool",
+        Bson::DateTime(_) => "date",
+        Bson::Null => "null",
+        Bson::RegularExpression(_) => "regex",
+        Bson::DbPointer(_) => "dbPointer",
+        Bson::JavaScriptCode(_) => "javascript",
+        Bson::Symbol(_) => "symbol",
+        Bson::JavaScriptCodeWithScope(_) => "javascriptWithScope",
+        Bson::Int32(_) => "int",
+        Bson::Timestamp(_) => "timestamp",
+        Bson::Int64(_) => "long",
+        Bson::Decimal128(_) => "decimal",
+        Bson::MinKey => "minKey",
+        Bson::MaxKey => "maxKey",
+    }
+}
+
+/// Walk through the value, and record the observed field paths.
+///
+/// Nested fields are joined by `.`, array elements are recorded with `[]` suffix.
+fn walk(
+    path: String,
+    val: &Bson,
+    stats: &mut BTreeMap<String, FieldStats>,
+    seen: &mut HashSet<String>,
+) {
+    let field = stats.entry(path.clone()).or_default();
+    field.observe(val);
+    if seen.insert(path.clone()) {
+        field.presence += 1;
+    }
+    match val {
+        Bson::Document(doc) => {
+            for (k, v) in doc {
+                walk(format!("{path}.{k}"), v, stats, seen)
+            }
+        }
+        Bson::Array(vals) => {
+            for v in vals {
+                walk(format!("{path}[]"), v, stats, seen)
+            }
+        }
+        _ => (),
+    }
+}
+
+impl SimplePluginCommand for Schema {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc schema"
+    }
+
+    fn description(&self) -> &str {
+        "infer schema of a mongodb collection by sampling documents"
+    }
+
+    fn extra_description(&self) -> &str {
+        "Nested fields are joined by `.`, array elements are reported with `[]` suffix. `types` are the percentages of observed values, `presence` is the ratio of sampled documents which contain the field."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        with_read_flags(Signature::build("mongoc schema"))
+            .optional(
+                "query object",
+                SyntaxShape::Record(vec![]),
+                "query object to filter documents before sampling",
+            )
+            .required_named(
+                "collection",
+                SyntaxShape::String,
+                "collection name",
+                Some('c'),
+            )
+            .named(
+                "db-handle",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name, can get from `mongoc list`",
+                Some('d'),
+            )
+            .named(
+                "sample",
+                SyntaxShape::Int,
+                "documents to sample, default is 1000",
+                None,
+            )
+            .input_output_type(Type::Nothing, Type::table())
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "infer schema of `students` collection",
+                example: "mongoc schema -c students",
+                result: None,
+            },
+            Example {
+                description: "infer schema of `teachers` with age `30`, sample 100 documents",
+                example: "mongoc schema {age: 30} -c teachers --sample 100",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let db_handle: Option<Value> = call.get_flag("db-handle")?;
+        let (handle_id, db) = match db_handle {
+            None => {
+                let handle_id = plugin.get_current()?;
+                (handle_id, plugin.get_handle(handle_id, call.head)?)
+            }
+            Some(db_handle) => {
+                let handle_id = plugin.resolve_handle_id(&db_handle)?;
+                (handle_id, plugin.get_handle(handle_id, db_handle.span())?)
+            }
+        };
+        let bson_options = BsonOptions::for_handle(plugin, engine, handle_id);
+        let coll: String = call
+            .get_flag("collection")?
+            .expect("already check existed.");
+        let sample: Spanned<i64> = call.get_flag("sample")?.unwrap_or(Spanned {
+            item: 1000,
+            span: call.head,
+        });
+        if sample.item <= 0 {
+            return Err(LabeledError::new("get invalid number")
+                .with_label("should be positive", sample.span));
+        }
+        let query: Record = call.opt(0)?.unwrap_or_default();
+        let mut pipeline = vec![];
+        if !query.is_empty() {
+            pipeline.push(doc! {"$match": value_to_doc(query, &bson_options.filter())?});
+        }
+        pipeline.push(doc! {"$sample": {"size": sample.item}});
+        let result = db
+            .collection_with_options::<Document>(&coll, collection_options(call)?)
+            .aggregate(pipeline)
+            .run()
+            .map_err(|e| mongo_error(e, call))?;
+
+        let mut stats = BTreeMap::new();
+        let mut sampled = 0;
+        for doc in result {
+            let doc = doc.map_err(|e| mongo_error(e, call))?;
+            sampled += 1;
+            let mut seen = HashSet::new();
+            for (k, v) in doc.iter() {
+                walk(k.clone(), v, &mut stats, &mut seen)
+            }
+        }
+        let rows = stats
+            .into_iter()
+            .map(|(path, field)| field.into_value(path, sampled, call.head, &bson_options))
+            .collect();
+        Ok(Value::list(rows, call.head))
+    }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Flag(name) if name == "collection" => {
+                super::get_collection_names_at_handle(plugin, engine, &call)
+            }
+            ArgType::Positional(0) => super::get_field_names_at_handle(plugin, engine, &call),
+            ArgType::Flag(name) if name == "db-handle" => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
+}
@@ -1,8 +1,7 @@
 use crate::MongoPlugin;
 use nu_plugin::{DynamicCompletionCall, EngineInterface, SimplePluginCommand};
 use nu_protocol::{
-    Category, DynamicSuggestion, LabeledError, Signature, Spanned, SyntaxShape, Type, Value,
-    engine::ArgType,
+    Category, DynamicSuggestion, LabeledError, Signature, SyntaxShape, Type, Value, engine::ArgType,
 };
 
 pub struct Select;
@@ -22,8 +21,8 @@ impl SimplePluginCommand for Select {
         Signature::build("mongoc select")
             .required(
                 "db-handle",
-                SyntaxShape::Int,
-                "database handle to seelct, can get from `mongoc list`",
+                SyntaxShape::OneOf(vec![SyntaxShape::Int, SyntaxShape::String]),
+                "database handle id or name to select, can get from `mongoc list`",
             )
             .input_output_type(Type::Nothing, Type::Nothing)
             .category(Category::Database)
@@ -36,8 +35,23 @@ impl SimplePluginCommand for Select {
         call: &nu_plugin::EvaluatedCall,
         _input: &Value,
     ) -> Result<Value, LabeledError> {
-        let handle_id: Spanned<i64> = call.req(0)?;
-        plugin.select_handle(handle_id.item as u8, handle_id.span)?;
+        let handle_id: Value = call.req(0)?;
+        plugin.select_handle(plugin.resolve_handle_id(&handle_id)?, handle_id.span())?;
         Ok(Value::nothing(call.head))
     }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &Self::Plugin,
+        _engine: &EngineInterface,
+        _call: DynamicCompletionCall,
+        arg_type: ArgType,
+        _experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        match arg_type {
+            ArgType::Positional(0) => super::get_handle_ids(plugin),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,66 @@
+use crate::MongoPlugin;
+use mongodb::bson::Uuid;
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{
+    Category, Example, LabeledError, Signature, Spanned, SyntaxShape, Type, Value, record,
+};
+
+pub struct UuidCmd;
+
+impl SimplePluginCommand for UuidCmd {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        "mongoc uuid"
+    }
+
+    fn description(&self) -> &str {
+        "make a UUID literal, which can be used in query and documents"
+    }
+
+    fn extra_description(&self) -> &str {
+        "The literal is a record like `{$uuid: 2d3f6a3e-6c1b-4f0a-9a2e-3b8c5d7e9f10}`, it's stored as binary with UUID subtype.  A new random UUID is generated if string is not given."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("mongoc uuid")
+            .optional("uuid", SyntaxShape::String, "UUID string")
+            .input_output_type(Type::Nothing, Type::record())
+            .category(Category::Database)
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        vec![
+            Example {
+                description: "find a session by UUID",
+                example: "mongoc find-one {session: (mongoc uuid 2d3f6a3e-6c1b-4f0a-9a2e-3b8c5d7e9f10)} -c sessions",
+                result: None,
+            },
+            Example {
+                description: "generate a new random UUID",
+                example: "mongoc uuid",
+                result: None,
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        _plugin: &MongoPlugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        _input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let uuid: Option<Spanned<String>> = call.opt(0)?;
+        let uuid = match uuid {
+            None => Uuid::new(),
+            Some(uuid) => Uuid::parse_str(&uuid.item).map_err(|e| {
+                LabeledError::new("invalid UUID").with_label(format!("{e}"), uuid.span)
+            })?,
+        };
+        Ok(Value::record(
+            record! { "$uuid" => Value::string(uuid.to_string(), call.head) },
+            call.head,
+        ))
+    }
+}
@@ -1,77 +1,209 @@
-use mongodb::bson::{Bson, Document};
-use nu_protocol::{LabeledError, Record, Span, Value};
+use crate::MongoPlugin;
+use mongodb::bson::{
+    Binary, Bson, DateTime, Decimal128, Document, Regex, Uuid, UuidRepresentation, oid::ObjectId,
+    spec::BinarySubtype,
+};
+use nu_plugin::EngineInterface;
+use nu_protocol::{LabeledError, Range, Record, Span, Value, ast::PathMember, record};
+use std::collections::HashMap;
+use std::ops::Bound;
+use std::sync::Arc;
 
-pub fn doc_to_value(doc: Document, span: Span) -> Value {
+/// How nushell integers are written to mongodb.
+#[derive(Clone, Copy, Default)]
+pub enum IntegerMode {
+    /// Int32 when the value fits, otherwise Int64, like mongosh.
+    #[default]
+    Auto,
+    /// always Int64.
+    Int64,
+}
+
+impl IntegerMode {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "auto" | "int32" => Some(Self::Auto),
+            "int64" => Some(Self::Int64),
+            _ => None,
+        }
+    }
+}
+
+/// Conversion settings, configured in `$env.config.plugins.mongo`.
+#[derive(Clone)]
+pub struct BsonOptions {
+    pub integer: IntegerMode,
+    /// integer mode of fields, keyed by dotted field names, e.g: `{stats.views: int64}`.
+    pub int_fields: Arc<HashMap<String, IntegerMode>>,
+    /// convert ranges to predicates, they're only valid in filters.
+    pub ranges: bool,
+    /// how UUIDs are stored, configured per handle by `mongoc open --uuid-representation`.
+    pub uuid: UuidRepresentation,
+    /// render ObjectId, Decimal128 and UUID as typed literals, so they're written back unchanged.
+    pub lossless: bool,
+}
+
+impl Default for BsonOptions {
+    fn default() -> Self {
+        Self {
+            integer: IntegerMode::default(),
+            int_fields: Arc::default(),
+            ranges: false,
+            uuid: UuidRepresentation::Standard,
+            lossless: false,
+        }
+    }
+}
+
+impl BsonOptions {
+    pub fn from_engine(engine: &EngineInterface) -> Self {
+        let config = engine.get_plugin_config().ok().flatten();
+        let get = |name: &str| config.as_ref().and_then(|c| c.get_data_by_key(name));
+        let integer = get("integer_mode")
+            .as_ref()
+            .and_then(|v| IntegerMode::parse(v.as_str().ok()?))
+            .unwrap_or_default();
+        let int_fields = match get("int_fields") {
+            Some(Value::Record { val, .. }) => val
+                .iter()
+                .filter_map(|(field, mode)| {
+                    Some((field.clone(), IntegerMode::parse(mode.as_str().ok()?)?))
+                })
+                .collect(),
+            _ => HashMap::new(),
+        };
+        Self {
+            integer,
+            int_fields: Arc::new(int_fields),
+            lossless: get("lossless").and_then(|v| v.as_bool().ok()) == Some(true),
+            ..Self::default()
+        }
+    }
+
+    /// Get options of the handle, e.g: UUID representation.
+    pub fn for_handle(plugin: &MongoPlugin, engine: &EngineInterface, handle: u32) -> Self {
+        let options = Self::from_engine(engine);
+        match plugin.get_uuid_representation(handle) {
+            Some(uuid) => Self { uuid, ..options },
+            None => options,
+        }
+    }
+
+    /// Options to convert filters, e.g: `{age: 18..30}` is `{age: {$gte: 18, $lte: 30}}`.
+    pub fn filter(&self) -> Self {
+        Self {
+            ranges: true,
+            ..self.clone()
+        }
+    }
+
+    /// Convert integer of the field, which can be hinted by `int_fields`.
+    pub fn int_at(&self, path: &str, i: i64) -> Bson {
+        match self.int_fields.get(path).copied().unwrap_or(self.integer) {
+            IntegerMode::Auto => i32::try_from(i).map_or(Bson::Int64(i), Bson::Int32),
+            IntegerMode::Int64 => Bson::Int64(i),
+        }
+    }
+}
+
+pub fn doc_to_value(doc: Document, span: Span, options: &BsonOptions) -> Value {
     let mut rec = Record::new();
 
     for (k, v) in doc {
-        let val = match v {
-            Bson::Null => Value::nothing(span),
-            Bson::Double(n) => Value::float(n, span),
-            Bson::String(s) => Value::string(s, span),
-            Bson::Boolean(v) => Value::bool(v, span),
-            Bson::Int32(i) => Value::int(i.into(), span),
-            Bson::Int64(i) => Value::int(i.into(), span),
-            Bson::ObjectId(oid) => Value::string(oid.to_string(), span),
-            Bson::Document(d) => doc_to_value(d, span),
-            Bson::DateTime(dt) => Value::date(dt.to_chrono().into(), span),
-            Bson::Binary(b) => Value::binary(b.bytes, span),
-            other => Value::string(other.to_string(), span),
-        };
-        rec.push(k, val);
+        rec.push(k, bson_to_value(v, span, options));
     }
     Value::record(rec, span)
 }
 
-fn to_bson(v: Value) -> Result<Bson, LabeledError> {
+pub fn bson_to_value(v: Bson, span: Span, options: &BsonOptions) -> Value {
+    match v {
+        Bson::Null => Value::nothing(span),
+        Bson::Double(n) => Value::float(n, span),
+        Bson::String(s) => Value::string(s, span),
+        Bson::Boolean(v) => Value::bool(v, span),
+        Bson::Int32(i) => Value::int(i.into(), span),
+        Bson::Int64(i) => Value::int(i, span),
+        Bson::ObjectId(oid) if options.lossless => Value::record(
+            record! { "$oid" => Value::string(oid.to_hex(), span) },
+            span,
+        ),
+        Bson::ObjectId(oid) => Value::string(oid.to_string(), span),
+        Bson::Decimal128(d) if options.lossless => Value::record(
+            record! { "$numberDecimal" => Value::string(d.to_string(), span) },
+            span,
+        ),
+        Bson::Document(d) => doc_to_value(d, span, options),
+        Bson::DateTime(dt) => Value::date(dt.to_chrono().into(), span),
+        Bson::Binary(b) => binary_to_value(b, span, options),
+        other => Value::string(other.to_string(), span),
+    }
+}
+
+/// Generic binaries are nushell binaries, UUIDs are strings, and other subtypes are kept in
+/// `{$binary: {base64, subType}}` records, so they're written back unchanged.
+fn binary_to_value(b: Binary, span: Span, options: &BsonOptions) -> Value {
+    let uuid = match b.subtype {
+        BinarySubtype::Generic => return Value::binary(b.bytes, span),
+        BinarySubtype::Uuid => b.to_uuid().ok(),
+        // legacy UUIDs can't be decoded without knowing the byte order.
+        BinarySubtype::UuidOld => b.to_uuid_with_representation(options.uuid).ok(),
+        _ => None,
+    };
+    match uuid {
+        Some(uuid) if options.lossless => Value::record(
+            record! { "$uuid" => Value::string(uuid.to_string(), span) },
+            span,
+        ),
+        Some(uuid) => Value::string(uuid.to_string(), span),
+        None => {
+            let json = Bson::Binary(b).into_relaxed_extjson();
+            let field = |name: &str| {
+                Value::string(json["$binary"][name].as_str().unwrap_or_default(), span)
+            };
+            Value::record(
+                record! {
+                    "$binary" => Value::record(
+                        record! { "base64" => field("base64"), "subType" => field("subType") },
+                        span,
+                    ),
+                },
+                span,
+            )
+        }
+    }
+}
+
+/// Convert nushell value to bson, filesizes are written as bytes, durations as milliseconds,
+/// and cell paths as dotted field names.
+///
+/// `path` is the field of the value, which is used to look up `int_fields`.
+pub fn to_bson(v: Value, options: &BsonOptions, path: &str) -> Result<Bson, LabeledError> {
     let val_span = v.span();
     let bson_val = match v {
-        Value::Record { val, .. } => Bson::Document(value_to_doc(val.into_owned())?),
-        Value::Int { val, .. } => Bson::Int64(val),
+        Value::Record { val, .. } => match typed_literal(&val, val_span, options)? {
+            Some(literal) => literal,
+            None => Bson::Document(record_to_doc(val.into_owned(), options, path)?),
+        },
+        Value::Int { val, .. } => options.int_at(path, val),
+        Value::Filesize { val, .. } => options.int_at(path, val.get()),
+        Value::Duration { val, .. } => options.int_at(path, val / 1_000_000),
         Value::Bool { val, .. } => Bson::Boolean(val),
-        Value::Float { val, .. } => Bson::Double(val),
-        Value::String { val, .. } | Value::Glob { val, .. } => {
-            // FIXME: there are some issues on record value interpolation
-            // see more: https://github.com/nushell/nushell/issues/13602
-            // // may parse as an mongodb ObjectId
-            // if val.starts_with("ObjectId(\"") && val.ends_with("\")") {
-            //     let object_id = val
-            //         .trim_start_matches("ObjectId(\"")
-            //         .trim_end_matches("\")");
-            //     match bson::oid::ObjectId::parse_str(object_id) {
-            //         Err(e) => {
-            //             return Err(LabeledError::new(format!(
-            //                 "invalid ObjectId, detailed: {e}"
-            //             )))
-            //         }
-            //         Ok(object_id) => Bson::ObjectId(object_id),
-            //     let object_id = val.trim_start_matches("ObjectId(\'").trim_end_matches("')");
-            //     match bson::oid::ObjectId::parse_str(object_id) {
-            //         Err(e) => {
-            //             return Err(LabeledError::new(format!(
-            //                 "invalid ObjectId, detailed: {e}"
-            //             )))
-            //         }
-            //         Ok(object_id) => Bson::ObjectId(object_id),
-            //     }
-            // } else {
-            //     Bson::String(val)
-            // }
-            //
-            // A workaround
-            if val.starts_with("ObjectId") {
-                let object_id = val.trim_start_matches("ObjectId");
-                match bson::oid::ObjectId::parse_str(object_id) {
-                    Err(e) => {
-                        return Err(LabeledError::new(format!("invalid ObjectId"))
-                            .with_label(format!("{e}"), val_span));
-                    }
-                    Ok(object_id) => Bson::ObjectId(object_id),
-                }
-            } else {
-                Bson::String(val)
-            }
+        Value::Nothing { .. } => Bson::Null,
+        Value::CellPath { val, .. } => Bson::String(
+            val.members
+                .iter()
+                .map(|member| match member {
+                    PathMember::String { val, .. } => val.clone(),
+                    PathMember::Int { val, .. } => val.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("."),
+        ),
+        Value::Range { val, .. } if options.ranges => {
+            range_predicate(&val, options, path, val_span)?
         }
+        Value::Float { val, .. } => Bson::Double(val),
+        Value::String { val, .. } | Value::Glob { val, .. } => Bson::String(val),
         Value::Date { val, .. } => {
             Bson::DateTime(bson::DateTime::from_chrono::<chrono::Utc>(val.into()))
         }
@@ -82,27 +214,574 @@ fn to_bson(v: Value) -> Result<Bson, LabeledError> {
         Value::List { vals, .. } => {
             let mut array_vals = vec![];
             for v in vals {
-                array_vals.push(to_bson(v)?)
+                array_vals.push(to_bson(v, options, path)?)
             }
             Bson::Array(array_vals)
         }
         other => {
-            return Err(
-                LabeledError::new(format!("can't convert to mongo doc")).with_label(
-                    format!("invalid value type: {}", other.get_type()),
-                    val_span,
-                ),
-            );
+            return Err(LabeledError::new("can't convert to mongo doc").with_label(
+                format!("invalid value type: {}", other.get_type()),
+                val_span,
+            ));
         }
     };
     Ok(bson_val)
 }
 
-pub fn value_to_doc(val: Record) -> Result<Document, LabeledError> {
+/// Convert range to predicate, e.g: `18..<30` is `{$gte: 18, $lt: 30}`.
+fn range_predicate(
+    range: &Range,
+    options: &BsonOptions,
+    path: &str,
+    span: Span,
+) -> Result<Bson, LabeledError> {
+    let unsupported_step = || {
+        LabeledError::new("unsupported range").with_label(
+            "only ascending ranges with step 1 can be used in query",
+            span,
+        )
+    };
+    let mut predicate = Document::new();
+    match range {
+        Range::IntRange(r) => {
+            if r.step() != 1 {
+                return Err(unsupported_step());
+            }
+            predicate.insert("$gte", options.int_at(path, r.start()));
+            match r.end() {
+                Bound::Included(end) => predicate.insert("$lte", options.int_at(path, end)),
+                Bound::Excluded(end) => predicate.insert("$lt", options.int_at(path, end)),
+                Bound::Unbounded => None,
+            };
+        }
+        Range::FloatRange(r) => {
+            if r.step() != 1.0 {
+                return Err(unsupported_step());
+            }
+            predicate.insert("$gte", r.start());
+            match r.end() {
+                Bound::Included(end) => predicate.insert("$lte", end),
+                Bound::Excluded(end) => predicate.insert("$lt", end),
+                Bound::Unbounded => None,
+            };
+        }
+    }
+    Ok(Bson::Document(predicate))
+}
+
+pub fn value_to_doc(val: Record, options: &BsonOptions) -> Result<Document, LabeledError> {
+    record_to_doc(val, options, "")
+}
+
+/// Operators don't change the field path, e.g: `age` in `{age: {$in: [1, 2]}}`, and `a.b` in
+/// `{$set: {a.b: 1}}`.
+fn record_to_doc(val: Record, options: &BsonOptions, path: &str) -> Result<Document, LabeledError> {
     let mut doc = Document::new();
-    for (k, v) in Record::clone(&val).into_iter() {
-        let bson_val = to_bson(v)?;
+    for (k, v) in val.into_iter() {
+        let field_path = match (k.starts_with('$'), path) {
+            (true, _) => path.to_string(),
+            (false, "") => k.clone(),
+            (false, _) => format!("{path}.{k}"),
+        };
+        let bson_val = to_bson(v, options, &field_path)?;
         doc.insert(k, bson_val);
     }
     Ok(doc)
 }
+
+/// Convert typed literals in extended json style, e.g: `{$oid: <hex>}`, `{$date: <date>}`,
+/// `{$numberDecimal: <string>}`, `{$numberInt: <int>}`, `{$numberLong: <int>}`,
+/// `{$regex: <pattern>, $options: <flags>}`, `{$uuid: <string>}` and `{$binary: {base64, subType}}`.
+///
+/// Returns `None` if the record is not a typed literal.
+fn typed_literal(
+    rec: &Record,
+    span: Span,
+    options: &BsonOptions,
+) -> Result<Option<Bson>, LabeledError> {
+    let invalid = |name: &str, e: String| {
+        LabeledError::new(format!("invalid {name} literal")).with_label(e, span)
+    };
+    let string_of = |name: &str, v: &Value| {
+        v.as_str()
+            .map(|s| s.to_string())
+            .map_err(|_| invalid(name, format!("expected string, got {}", v.get_type())))
+    };
+    let field = |key: &str| rec.get(key).expect("key is already checked");
+    let keys: Vec<&str> = rec.columns().map(|k| k.as_str()).collect();
+    let literal = match keys.as_slice() {
+        ["$oid"] => {
+            let hex = string_of("$oid", field("$oid"))?;
+            Bson::ObjectId(ObjectId::parse_str(&hex).map_err(|e| invalid("$oid", format!("{e}")))?)
+        }
+        ["$uuid"] => {
+            let uuid = string_of("$uuid", field("$uuid"))?;
+            Bson::Binary(Binary::from_uuid_with_representation(
+                Uuid::parse_str(&uuid).map_err(|e| invalid("$uuid", format!("{e}")))?,
+                options.uuid,
+            ))
+        }
+        ["$numberDecimal"] => {
+            let number = match field("$numberDecimal") {
+                Value::Int { val, .. } => val.to_string(),
+                Value::Float { val, .. } => val.to_string(),
+                other => string_of("$numberDecimal", other)?,
+            };
+            Bson::Decimal128(
+                number
+                    .parse::<Decimal128>()
+                    .map_err(|e| invalid("$numberDecimal", format!("{e}")))?,
+            )
+        }
+        ["$binary"] => {
+            let binary = field("$binary")
+                .as_record()
+                .map_err(|_| invalid("$binary", "expected {base64, subType} record".into()))?;
+            let mut json = serde_json::Map::new();
+            for name in ["base64", "subType"] {
+                let value = binary
+                    .get(name)
+                    .ok_or_else(|| invalid("$binary", format!("`{name}` is required")))?;
+                json.insert(name.into(), string_of("$binary", value)?.into());
+            }
+            Bson::try_from(serde_json::json!({ "$binary": json }))
+                .map_err(|e| invalid("$binary", format!("{e}")))?
+        }
+        ["$numberInt"] | ["$numberLong"] => {
+            let name = keys[0];
+            let number = match field(name) {
+                Value::Int { val, .. } => *val,
+                other => string_of(name, other)?
+                    .parse::<i64>()
+                    .map_err(|e| invalid(name, format!("{e}")))?,
+            };
+            if name == "$numberLong" {
+                Bson::Int64(number)
+            } else {
+                Bson::Int32(i32::try_from(number).map_err(|_| {
+                    invalid(
+                        name,
+                        format!("should be between {} and {}", i32::MIN, i32::MAX),
+                    )
+                })?)
+            }
+        }
+        ["$date"] => Bson::DateTime(match field("$date").clone() {
+            Value::Date { val, .. } => DateTime::from_chrono::<chrono::Utc>(val.into()),
+            Value::Int { val, .. } => DateTime::from_millis(val),
+            other => {
+                let date = string_of("$date", &other)?;
+                let date = chrono::DateTime::parse_from_rfc3339(&date)
+                    .map_err(|e| invalid("$date", format!("{e}")))?;
+                DateTime::from_chrono(date)
+            }
+        }),
+        ["$regex"] | ["$regex", "$options"] | ["$options", "$regex"] => {
+            let pattern = string_of("$regex", field("$regex"))?;
+            let options = match rec.get("$options") {
+                None => String::new(),
+                Some(options) => string_of("$options", options)?,
+            };
+            let mut options: Vec<char> = options.chars().collect();
+            // options must be stored in alphabetical order.
+            options.sort_unstable();
+            Bson::RegularExpression(Regex {
+                pattern,
+                options: options.into_iter().collect(),
+            })
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(literal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+    use nu_protocol::ast::RangeInclusion;
+
+    const OID: &str = "65a1b2c3d4e5f60718293a4b";
+    const UUID: &str = "00112233-4455-6677-8899-aabbccddeeff";
+
+    fn literal(rec: Record) -> Result<Bson, LabeledError> {
+        to_bson(Value::test_record(rec), &BsonOptions::default(), "")
+    }
+
+    /// Assert the literal is rejected, with the message and the label.
+    fn assert_invalid(rec: Record, msg: &str, label: &str) {
+        let err = literal(rec).expect_err("literal should be rejected");
+        assert_eq!(err.msg, msg);
+        assert!(
+            err.labels[0].text.contains(label),
+            "unexpected label: {}",
+            err.labels[0].text
+        );
+    }
+
+    fn s(v: &str) -> Value {
+        Value::test_string(v)
+    }
+
+    #[test]
+    fn oid_literal() {
+        assert_eq!(
+            literal(record! { "$oid" => s(OID) }).unwrap(),
+            Bson::ObjectId(ObjectId::parse_str(OID).unwrap())
+        );
+        assert_invalid(record! { "$oid" => s("xyz") }, "invalid $oid literal", "");
+        assert_invalid(
+            record! { "$oid" => Value::test_int(1) },
+            "invalid $oid literal",
+            "expected string, got int",
+        );
+    }
+
+    #[test]
+    fn uuid_literal() {
+        let uuid = Uuid::parse_str(UUID).unwrap();
+        assert_eq!(
+            literal(record! { "$uuid" => s(UUID) }).unwrap(),
+            Bson::Binary(Binary::from_uuid(uuid))
+        );
+        assert_invalid(
+            record! { "$uuid" => s("0011") },
+            "invalid $uuid literal",
+            "",
+        );
+    }
+
+    #[test]
+    fn decimal_literal() {
+        let decimal = |v: Value| match literal(record! { "$numberDecimal" => v }).unwrap() {
+            Bson::Decimal128(d) => d.to_string(),
+            other => panic!("expected decimal, got {other}"),
+        };
+        assert_eq!(decimal(s("1.50")), "1.50");
+        assert_eq!(decimal(Value::test_int(3)), "3");
+        assert_invalid(
+            record! { "$numberDecimal" => s("abc") },
+            "invalid $numberDecimal literal",
+            "",
+        );
+    }
+
+    #[test]
+    fn binary_literal() {
+        let binary = record! { "base64" => s("AQID"), "subType" => s("80") };
+        assert_eq!(
+            literal(record! { "$binary" => Value::test_record(binary) }).unwrap(),
+            Bson::Binary(Binary {
+                subtype: BinarySubtype::UserDefined(0x80),
+                bytes: vec![1, 2, 3],
+            })
+        );
+        assert_invalid(
+            record! { "$binary" => Value::test_record(record! { "base64" => s("AQID") }) },
+            "invalid $binary literal",
+            "`subType` is required",
+        );
+        assert_invalid(
+            record! { "$binary" => s("AQID") },
+            "invalid $binary literal",
+            "expected {base64, subType} record",
+        );
+        let binary = record! { "base64" => s("not base64!"), "subType" => s("00") };
+        assert_invalid(
+            record! { "$binary" => Value::test_record(binary) },
+            "invalid $binary literal",
+            "",
+        );
+    }
+
+    #[test]
+    fn number_literals() {
+        assert_eq!(
+            literal(record! { "$numberInt" => s("42") }).unwrap(),
+            Bson::Int32(42)
+        );
+        assert_eq!(
+            literal(record! { "$numberLong" => s("5000000000") }).unwrap(),
+            Bson::Int64(5_000_000_000)
+        );
+        assert_eq!(
+            literal(record! { "$numberLong" => Value::test_int(7) }).unwrap(),
+            Bson::Int64(7)
+        );
+        assert_invalid(
+            record! { "$numberInt" => Value::test_int(5_000_000_000) },
+            "invalid $numberInt literal",
+            "should be between",
+        );
+        assert_invalid(
+            record! { "$numberLong" => s("1.5") },
+            "invalid $numberLong literal",
+            "",
+        );
+    }
+
+    #[test]
+    fn date_literal() {
+        let expected = Bson::DateTime(DateTime::from_millis(1_704_164_645_000));
+        assert_eq!(
+            literal(record! { "$date" => s("2024-01-02T03:04:05Z") }).unwrap(),
+            expected
+        );
+        assert_eq!(
+            literal(record! { "$date" => Value::test_int(1_704_164_645_000) }).unwrap(),
+            expected
+        );
+        assert_invalid(
+            record! { "$date" => s("2024-01-02") },
+            "invalid $date literal",
+            "",
+        );
+    }
+
+    #[test]
+    fn regex_literal() {
+        assert_eq!(
+            literal(record! { "$regex" => s("^a"), "$options" => s("xi") }).unwrap(),
+            Bson::RegularExpression(Regex {
+                pattern: "^a".into(),
+                options: "ix".into(),
+            })
+        );
+        assert_eq!(
+            literal(record! { "$options" => s("i"), "$regex" => s("^a") }).unwrap(),
+            Bson::RegularExpression(Regex {
+                pattern: "^a".into(),
+                options: "i".into(),
+            })
+        );
+        assert_invalid(
+            record! { "$regex" => Value::test_int(1) },
+            "invalid $regex literal",
+            "expected string",
+        );
+    }
+
+    #[test]
+    fn records_with_other_keys_are_documents() {
+        assert_eq!(
+            literal(record! { "$oid" => s(OID), "a" => Value::test_int(1) }).unwrap(),
+            Bson::Document(doc! { "$oid": OID, "a": 1 })
+        );
+    }
+
+    fn range(start: Value, next: Value, end: Value, inclusion: RangeInclusion) -> Value {
+        let span = Span::test_data();
+        Value::range(Range::new(start, next, end, inclusion, span).unwrap(), span)
+    }
+
+    fn predicate(range: Value, options: &BsonOptions) -> Result<Bson, LabeledError> {
+        let options = BsonOptions {
+            ranges: true,
+            ..options.clone()
+        };
+        to_bson(range, &options, "age")
+    }
+
+    #[test]
+    fn int_range_predicates() {
+        let nothing = Value::test_nothing;
+        let int = Value::test_int;
+        let options = BsonOptions::default();
+        assert_eq!(
+            predicate(
+                range(int(18), nothing(), int(30), RangeInclusion::RightExclusive),
+                &options
+            )
+            .unwrap(),
+            Bson::Document(doc! { "$gte": 18, "$lt": 30 })
+        );
+        assert_eq!(
+            predicate(
+                range(int(18), nothing(), int(30), RangeInclusion::Inclusive),
+                &options
+            )
+            .unwrap(),
+            Bson::Document(doc! { "$gte": 18, "$lte": 30 })
+        );
+        assert_eq!(
+            predicate(
+                range(int(18), nothing(), nothing(), RangeInclusion::Inclusive),
+                &options
+            )
+            .unwrap(),
+            Bson::Document(doc! { "$gte": 18 })
+        );
+
+        let int64 = BsonOptions {
+            int_fields: Arc::new(HashMap::from([("age".to_string(), IntegerMode::Int64)])),
+            ..Default::default()
+        };
+        assert_eq!(
+            predicate(
+                range(int(18), nothing(), int(30), RangeInclusion::Inclusive),
+                &int64
+            )
+            .unwrap(),
+            Bson::Document(doc! { "$gte": 18i64, "$lte": 30i64 })
+        );
+    }
+
+    #[test]
+    fn float_range_predicates() {
+        let nothing = Value::test_nothing;
+        let float = Value::test_float;
+        let options = BsonOptions::default();
+        assert_eq!(
+            predicate(
+                range(
+                    float(1.5),
+                    nothing(),
+                    float(2.5),
+                    RangeInclusion::RightExclusive
+                ),
+                &options
+            )
+            .unwrap(),
+            Bson::Document(doc! { "$gte": 1.5, "$lt": 2.5 })
+        );
+        assert_eq!(
+            predicate(
+                range(float(1.5), nothing(), float(2.5), RangeInclusion::Inclusive),
+                &options
+            )
+            .unwrap(),
+            Bson::Document(doc! { "$gte": 1.5, "$lte": 2.5 })
+        );
+        assert_eq!(
+            predicate(
+                range(float(1.5), nothing(), nothing(), RangeInclusion::Inclusive),
+                &options
+            )
+            .unwrap(),
+            Bson::Document(doc! { "$gte": 1.5 })
+        );
+    }
+
+    #[test]
+    fn unsupported_ranges() {
+        let int = Value::test_int;
+        let options = BsonOptions::default();
+        let stepped = range(int(1), int(3), int(9), RangeInclusion::Inclusive);
+        assert_eq!(
+            predicate(stepped, &options).unwrap_err().msg,
+            "unsupported range"
+        );
+        let descending = range(
+            int(30),
+            Value::test_nothing(),
+            int(18),
+            RangeInclusion::Inclusive,
+        );
+        assert_eq!(
+            predicate(descending, &options).unwrap_err().msg,
+            "unsupported range"
+        );
+        // ranges are only converted in filters.
+        let plain = range(
+            int(1),
+            Value::test_nothing(),
+            int(9),
+            RangeInclusion::Inclusive,
+        );
+        assert!(to_bson(plain, &options, "age").is_err());
+    }
+
+    /// Render the binary, and write the rendered value back.
+    fn round_trip(binary: Binary, options: &BsonOptions) -> (Value, Bson) {
+        let value = bson_to_value(Bson::Binary(binary), Span::test_data(), options);
+        let written = to_bson(value.clone(), options, "").unwrap();
+        (value, written)
+    }
+
+    fn uuid_options(uuid: UuidRepresentation, lossless: bool) -> BsonOptions {
+        BsonOptions {
+            uuid,
+            lossless,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn generic_binary_round_trip() {
+        let binary = Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: vec![1, 2, 3],
+        };
+        let (value, written) = round_trip(binary.clone(), &BsonOptions::default());
+        assert_eq!(value, Value::test_binary(vec![1, 2, 3]));
+        assert_eq!(written, Bson::Binary(binary));
+    }
+
+    #[test]
+    fn standard_uuid_round_trip() {
+        let binary = Binary::from_uuid(Uuid::parse_str(UUID).unwrap());
+        let (value, _) = round_trip(binary.clone(), &BsonOptions::default());
+        assert_eq!(value, s(UUID));
+
+        let (value, written) = round_trip(
+            binary.clone(),
+            &uuid_options(UuidRepresentation::Standard, true),
+        );
+        assert_eq!(value, Value::test_record(record! { "$uuid" => s(UUID) }));
+        assert_eq!(written, Bson::Binary(binary));
+    }
+
+    #[test]
+    fn legacy_uuid_round_trip() {
+        let uuid = Uuid::parse_str(UUID).unwrap();
+        for representation in [
+            UuidRepresentation::JavaLegacy,
+            UuidRepresentation::CSharpLegacy,
+            UuidRepresentation::PythonLegacy,
+        ] {
+            let binary = Binary::from_uuid_with_representation(uuid, representation);
+            assert_eq!(binary.subtype, BinarySubtype::UuidOld);
+            let (value, _) = round_trip(binary.clone(), &uuid_options(representation, false));
+            assert_eq!(value, s(UUID));
+
+            let (value, written) = round_trip(binary.clone(), &uuid_options(representation, true));
+            assert_eq!(value, Value::test_record(record! { "$uuid" => s(UUID) }));
+            assert_eq!(written, Bson::Binary(binary));
+        }
+    }
+
+    #[test]
+    fn legacy_uuid_without_representation_keeps_subtype() {
+        let binary = Binary::from_uuid_with_representation(
+            Uuid::parse_str(UUID).unwrap(),
+            UuidRepresentation::JavaLegacy,
+        );
+        let (value, written) = round_trip(binary.clone(), &BsonOptions::default());
+        let rendered = value.as_record().unwrap().get("$binary").unwrap();
+        assert_eq!(rendered.as_record().unwrap().get("subType"), Some(&s("03")));
+        assert_eq!(written, Bson::Binary(binary));
+    }
+
+    #[test]
+    fn other_subtypes_round_trip() {
+        for subtype in [
+            BinarySubtype::Function,
+            BinarySubtype::Md5,
+            BinarySubtype::Encrypted,
+            BinarySubtype::Sensitive,
+            BinarySubtype::UserDefined(0x80),
+        ] {
+            let binary = Binary {
+                subtype,
+                bytes: vec![1, 2, 3],
+            };
+            let (value, written) = round_trip(binary.clone(), &BsonOptions::default());
+            let rendered = value.as_record().unwrap().get("$binary").unwrap();
+            assert_eq!(
+                rendered.as_record().unwrap().get("base64"),
+                Some(&s("AQID"))
+            );
+            assert_eq!(written, Bson::Binary(binary));
+        }
+    }
+}
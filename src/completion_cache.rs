@@ -0,0 +1,161 @@
+use nu_plugin::EngineInterface;
+use nu_protocol::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock, mpsc};
+use std::time::{Duration, Instant};
+
+const DEFAULT_TTL: Duration = Duration::from_secs(60);
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+const DEFAULT_SERVER_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) enum CacheKey {
+    CollectionNames(u32),
+    FieldNames(u32, String),
+}
+
+impl CacheKey {
+    fn handle(&self) -> u32 {
+        match self {
+            CacheKey::CollectionNames(id) | CacheKey::FieldNames(id, _) => *id,
+        }
+    }
+}
+
+struct Entry {
+    names: Vec<String>,
+    fetched_at: Instant,
+}
+
+/// Completion settings, configured in `$env.config.plugins.mongo`.
+pub(crate) struct CompletionConfig {
+    /// how long cached completions are considered fresh.
+    pub(crate) ttl: Duration,
+    /// how long to wait for the server, stale completions are returned after timeout.
+    pub(crate) timeout: Duration,
+    /// server selection timeout of completion queries.
+    pub(crate) server_timeout: Duration,
+}
+
+impl CompletionConfig {
+    pub(crate) fn from_engine(engine: &EngineInterface) -> Self {
+        let config = engine.get_plugin_config().ok().flatten();
+        let get_duration = |name: &str, default: Duration| {
+            config
+                .as_ref()
+                .and_then(|c| c.get_data_by_key(name))
+                .and_then(|v| match v {
+                    Value::Duration { val, .. } if val >= 0 => {
+                        Some(Duration::from_nanos(val as u64))
+                    }
+                    _ => None,
+                })
+                .unwrap_or(default)
+        };
+        Self {
+            ttl: get_duration("completion_cache_ttl", DEFAULT_TTL),
+            timeout: get_duration("completion_timeout", DEFAULT_TIMEOUT),
+            server_timeout: get_duration("completion_server_timeout", DEFAULT_SERVER_TIMEOUT),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Entries {
+    items: HashMap<CacheKey, Entry>,
+    /// bumped when cached items of the handle are invalidated, so in-flight refreshes started
+    /// before don't write old items back.
+    generations: HashMap<u32, u64>,
+}
+
+/// Cache of completion items, refreshed by background threads so slow servers don't block the prompt.
+#[derive(Default)]
+pub(crate) struct CompletionCache {
+    entries: Arc<RwLock<Entries>>,
+    refreshing: Arc<Mutex<HashSet<CacheKey>>>,
+}
+
+impl CompletionCache {
+    /// Get cached items of the key.
+    ///
+    /// If the cache is expired or missing, `fetch` is run in background, and it waits at most
+    /// `config.timeout` for the result.  Stale items are returned if the refresh doesn't finish in time.
+    pub(crate) fn get_or_refresh(
+        &self,
+        key: CacheKey,
+        config: &CompletionConfig,
+        fetch: impl FnOnce() -> Option<Vec<String>> + Send + 'static,
+    ) -> Option<Vec<String>> {
+        let (stale, generation) = {
+            let read_guard = self.entries.read().expect("read lock should success");
+            let generation = read_guard
+                .generations
+                .get(&key.handle())
+                .copied()
+                .unwrap_or_default();
+            match read_guard.items.get(&key) {
+                Some(entry) if entry.fetched_at.elapsed() < config.ttl => {
+                    return Some(entry.names.clone());
+                }
+                Some(entry) => (Some(entry.names.clone()), generation),
+                None => (None, generation),
+            }
+        };
+
+        let is_new_refresh = self
+            .refreshing
+            .lock()
+            .expect("lock should success")
+            .insert(key.clone());
+        if !is_new_refresh {
+            return stale;
+        }
+        let (sender, receiver) = mpsc::channel();
+        let entries = self.entries.clone();
+        let refreshing = self.refreshing.clone();
+        std::thread::spawn(move || {
+            let mut names = fetch();
+            {
+                let mut write_guard = entries.write().expect("write lock should success");
+                let current = write_guard
+                    .generations
+                    .get(&key.handle())
+                    .copied()
+                    .unwrap_or_default();
+                match &names {
+                    // collections are changed while fetching.
+                    Some(_) if current != generation => names = None,
+                    Some(names) => {
+                        write_guard.items.insert(
+                            key.clone(),
+                            Entry {
+                                names: names.clone(),
+                                fetched_at: Instant::now(),
+                            },
+                        );
+                    }
+                    None => (),
+                }
+            }
+            refreshing.lock().expect("lock should success").remove(&key);
+            // receiver may be gone after timeout.
+            let _ = sender.send(names);
+        });
+        match receiver.recv_timeout(config.timeout) {
+            Ok(Some(names)) => Some(names),
+            _ => stale,
+        }
+    }
+
+    /// Remove cached collection names of the handle, and field names of the given collection,
+    /// all cached items of the handle are removed if `coll` is None.
+    pub(crate) fn invalidate(&self, id: u32, coll: Option<&str>) {
+        let mut write_guard = self.entries.write().expect("write lock should success");
+        *write_guard.generations.entry(id).or_default() += 1;
+        write_guard.items.retain(|key, _| match (key, coll) {
+            (_, _) if key.handle() != id => true,
+            (CacheKey::FieldNames(_, name), Some(coll)) => name != coll,
+            _ => false,
+        });
+    }
+}
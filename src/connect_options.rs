@@ -0,0 +1,190 @@
+use mongodb::bson::{Document, UuidRepresentation};
+use mongodb::options::{
+    AuthMechanism, ClientOptions, Compressor, ReadConcern, ReadPreference, SelectionCriteria, Tls,
+    TlsOptions, WriteConcern,
+};
+use nu_protocol::LabeledError;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Options given to `mongoc open` besides connection string, they're saved with persisted handles.
+///
+/// Passwords are not part of it.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ConnectOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_preference: Option<ReadPreference>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_concern: Option<ReadConcern>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_mechanism: Option<String>,
+    /// mechanism properties, e.g: `ENVIRONMENT` of MONGODB-OIDC.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_properties: Option<Document>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_ca_file: Option<PathBuf>,
+    /// client certificate and private key file, also used by MONGODB-X509.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_cert_key_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_allow_invalid_certificates: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_allow_invalid_hostnames: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_selection_timeout: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_pool_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direct_connection: Option<bool>,
+    /// names of compressors, can be zstd, snappy or zlib.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compressors: Option<Vec<String>>,
+    /// how UUIDs are stored, can be standard, csharpLegacy, javaLegacy or pythonLegacy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid_representation: Option<String>,
+}
+
+/// Get UUID representation by name, names are the same as `uuidRepresentation` url option of other drivers.
+pub(crate) fn uuid_representation(name: &str) -> Option<UuidRepresentation> {
+    match name {
+        "standard" => Some(UuidRepresentation::Standard),
+        "csharpLegacy" => Some(UuidRepresentation::CSharpLegacy),
+        "javaLegacy" => Some(UuidRepresentation::JavaLegacy),
+        "pythonLegacy" => Some(UuidRepresentation::PythonLegacy),
+        _ => None,
+    }
+}
+
+/// Get compressor by name.
+pub(crate) fn compressor(name: &str) -> Option<Compressor> {
+    match name {
+        "zstd" => Some(Compressor::Zstd { level: None }),
+        "snappy" => Some(Compressor::Snappy),
+        "zlib" => Some(Compressor::Zlib { level: None }),
+        _ => None,
+    }
+}
+
+impl ConnectOptions {
+    /// Override options parsed from connection string.
+    pub(crate) fn apply(
+        &self,
+        options: &mut ClientOptions,
+        password: Option<String>,
+    ) -> Result<(), LabeledError> {
+        if let Some(read_preference) = &self.read_preference {
+            options.selection_criteria =
+                Some(SelectionCriteria::ReadPreference(read_preference.clone()));
+        }
+        if let Some(read_concern) = &self.read_concern {
+            options.read_concern = Some(read_concern.clone());
+        }
+        if let Some(write_concern) = &self.write_concern {
+            options.write_concern = Some(write_concern.clone());
+        }
+        if let Some(timeout) = self.connect_timeout {
+            options.connect_timeout = Some(timeout);
+        }
+        if let Some(timeout) = self.server_selection_timeout {
+            options.server_selection_timeout = Some(timeout);
+        }
+        if let Some(size) = self.max_pool_size {
+            options.max_pool_size = Some(size);
+        }
+        if let Some(app_name) = &self.app_name {
+            options.app_name = Some(app_name.clone());
+        }
+        if let Some(direct) = self.direct_connection {
+            options.direct_connection = Some(direct);
+        }
+        if let Some(names) = &self.compressors {
+            let mut compressors = vec![];
+            for name in names {
+                compressors.push(compressor(name).ok_or_else(|| {
+                    LabeledError::new(format!("unknown compressor `{name}`"))
+                        .with_help("Compressor can be zstd, snappy or zlib")
+                })?);
+            }
+            options.compressors = Some(compressors);
+        }
+
+        if self.user.is_some()
+            || self.auth_source.is_some()
+            || self.auth_mechanism.is_some()
+            || self.auth_properties.is_some()
+            || password.is_some()
+        {
+            let credential = options.credential.get_or_insert_default();
+            if let Some(user) = &self.user {
+                credential.username = Some(user.clone());
+            }
+            if let Some(source) = &self.auth_source {
+                credential.source = Some(source.clone());
+            }
+            if let Some(mechanism) = &self.auth_mechanism {
+                credential.mechanism = Some(
+                    AuthMechanism::from_str(mechanism)
+                        .map_err(|e| LabeledError::new(format!("{e}")))?,
+                );
+            }
+            if let Some(properties) = &self.auth_properties {
+                credential.mechanism_properties = Some(properties.clone());
+            }
+            if password.is_some() {
+                credential.password = password;
+            }
+        }
+
+        self.apply_tls(options)
+    }
+
+    fn apply_tls(&self, options: &mut ClientOptions) -> Result<(), LabeledError> {
+        let has_tls_options = self.tls_ca_file.is_some()
+            || self.tls_cert_key_file.is_some()
+            || self.tls_allow_invalid_certificates.is_some()
+            || self.tls_allow_invalid_hostnames.is_some();
+        if self.tls.is_none() && !has_tls_options {
+            return Ok(());
+        }
+        let mut tls_options = match options.tls.take() {
+            Some(Tls::Enabled(tls_options)) => tls_options,
+            _ => TlsOptions::default(),
+        };
+        if let Some(ca_file) = &self.tls_ca_file {
+            tls_options.ca_file_path = Some(ca_file.clone());
+        }
+        if let Some(cert_key_file) = &self.tls_cert_key_file {
+            tls_options.cert_key_file_path = Some(cert_key_file.clone());
+        }
+        if let Some(allow) = self.tls_allow_invalid_certificates {
+            tls_options.allow_invalid_certificates = Some(allow);
+        }
+        if let Some(allow) = self.tls_allow_invalid_hostnames {
+            #[cfg(feature = "openssl-tls")]
+            {
+                tls_options.allow_invalid_hostnames = Some(allow);
+            }
+            #[cfg(not(feature = "openssl-tls"))]
+            if allow {
+                return Err(LabeledError::new("allowing invalid hostnames is not supported")
+                    .with_help("Install the plugin with `openssl-tls` feature, or use `--tls-allow-invalid-certificates`"));
+            }
+        }
+        options.tls = Some(Tls::Enabled(tls_options));
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use mongodb::bson::Document;
+use mongodb::sync::Cursor;
+use nu_protocol::{LabeledError, Span};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Kept cursors idle for longer are closed, before the server kills them (10 minutes by default).
+const IDLE_TIMEOUT: Duration = Duration::from_secs(9 * 60);
+
+/// A cursor kept by `mongoc find --keep-cursor`, its documents are fetched by `mongoc next`.
+pub(crate) struct CursorEntry {
+    /// `None` while a batch is being fetched.
+    cursor: Option<Cursor<Document>>,
+    pub(crate) handle: u32,
+    pub(crate) coll: String,
+    pub(crate) batch_size: u64,
+    /// how many documents are returned.
+    pub(crate) fetched: u64,
+    pub(crate) last_used: Instant,
+}
+
+#[derive(Default)]
+pub(crate) struct CursorStore {
+    inner: Mutex<Cursors>,
+}
+
+#[derive(Default)]
+struct Cursors {
+    entries: HashMap<u32, CursorEntry>,
+    /// ids of cursors closed for being idle, so they're reported as expired.
+    expired: HashSet<u32>,
+    /// next cursor id to allocate, ids are never reused.
+    next_id: u32,
+}
+
+impl Cursors {
+    /// Close idle cursors, cursors which are being fetched are kept.
+    fn sweep(&mut self) {
+        let expired: Vec<u32> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.cursor.is_some() && entry.last_used.elapsed() > IDLE_TIMEOUT)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in expired {
+            self.entries.remove(&id);
+            self.expired.insert(id);
+        }
+    }
+}
+
+fn cursor_not_exist(span: Span) -> LabeledError {
+    LabeledError::new("cursor doesn't exist")
+        .with_label("not existed cursor", span)
+        .with_help("The cursor is closed after all documents are fetched, you can run `mongoc cursors` to list all available cursors")
+}
+
+impl CursorStore {
+    /// Keep the cursor, returns its id.
+    pub(crate) fn insert(
+        &self,
+        cursor: Cursor<Document>,
+        handle: u32,
+        coll: String,
+        batch_size: u64,
+        fetched: u64,
+    ) -> Result<u32, LabeledError> {
+        let mut guard = self.inner.lock().expect("lock should success");
+        guard.sweep();
+        let id = guard.next_id;
+        guard.next_id = id.checked_add(1).ok_or_else(|| {
+            LabeledError::new("cursor ids are exhausted")
+                .with_help("Please restart the plugin by running `plugin stop mongo`")
+        })?;
+        guard.entries.insert(
+            id,
+            CursorEntry {
+                cursor: Some(cursor),
+                handle,
+                coll,
+                batch_size,
+                fetched,
+                last_used: Instant::now(),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Take the cursor out to fetch documents, so other cursors are not blocked by the server.
+    pub(crate) fn take(
+        &self,
+        id: u32,
+        span: Span,
+    ) -> Result<(Cursor<Document>, u32, u64), LabeledError> {
+        let mut guard = self.inner.lock().expect("lock should success");
+        guard.sweep();
+        if guard.expired.contains(&id) {
+            return Err(LabeledError::new("cursor expired")
+                .with_label("cursor is closed after being idle", span)
+                .with_help(format!(
+                    "Cursors are closed after {} minutes without `mongoc next`, please run `mongoc find --keep-cursor` again",
+                    IDLE_TIMEOUT.as_secs() / 60
+                )));
+        }
+        let entry = guard
+            .entries
+            .get_mut(&id)
+            .ok_or_else(|| cursor_not_exist(span))?;
+        let cursor = entry.cursor.take().ok_or_else(|| {
+            LabeledError::new("cursor is busy")
+                .with_label("documents of the cursor are being fetched", span)
+        })?;
+        Ok((cursor, entry.handle, entry.batch_size))
+    }
+
+    /// Put the cursor back after fetching, it's closed if all documents are fetched.
+    pub(crate) fn put_back(
+        &self,
+        id: u32,
+        cursor: Cursor<Document>,
+        fetched: u64,
+        exhausted: bool,
+    ) {
+        let mut guard = self.inner.lock().expect("lock should success");
+        if exhausted {
+            guard.entries.remove(&id);
+            return;
+        }
+        // the cursor may be killed while fetching.
+        if let Some(entry) = guard.entries.get_mut(&id) {
+            entry.cursor = Some(cursor);
+            entry.fetched += fetched;
+            entry.last_used = Instant::now();
+        }
+    }
+
+    /// Close the cursor, the server side cursor is killed when it's dropped.
+    pub(crate) fn remove(&self, id: u32, span: Span) -> Result<(), LabeledError> {
+        let mut guard = self.inner.lock().expect("lock should success");
+        guard
+            .entries
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| cursor_not_exist(span))
+    }
+
+    /// Close all cursors of the handle.
+    pub(crate) fn remove_handle(&self, handle: u32) {
+        let mut guard = self.inner.lock().expect("lock should success");
+        guard.entries.retain(|_, entry| entry.handle != handle);
+    }
+
+    /// Run `f` with all cursors, ordered by id.
+    pub(crate) fn list<T>(&self, f: impl Fn(u32, &CursorEntry) -> T) -> Vec<T> {
+        let mut guard = self.inner.lock().expect("lock should success");
+        guard.sweep();
+        let mut ids: Vec<&u32> = guard.entries.keys().collect();
+        ids.sort();
+        ids.into_iter()
+            .map(|id| f(*id, &guard.entries[id]))
+            .collect()
+    }
+}
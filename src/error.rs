@@ -0,0 +1,170 @@
+use mongodb::error::{
+    BulkWriteError, Error, ErrorKind, InsertManyError, WriteConcernError, WriteFailure,
+};
+use nu_plugin::EvaluatedCall;
+use nu_protocol::{LabeledError, Span};
+
+/// Convert mongodb error to `LabeledError`.
+///
+/// Errors returned by server point at the query argument, or collection flag if there is no query,
+/// other errors point at the command itself.
+pub(crate) fn mongo_error(e: Error, call: &EvaluatedCall) -> LabeledError {
+    let arg_span = match call.positional.first() {
+        Some(query) => query.span(),
+        None => call
+            .get_flag_value("collection")
+            .map(|c| c.span())
+            .unwrap_or(call.head),
+    };
+    let span = if is_server_error(&e) {
+        arg_span
+    } else {
+        call.head
+    };
+    mongo_error_at(e, span)
+}
+
+fn is_server_error(e: &Error) -> bool {
+    matches!(
+        *e.kind,
+        ErrorKind::Command(_)
+            | ErrorKind::Write(_)
+            | ErrorKind::InsertMany(_)
+            | ErrorKind::BulkWrite(_)
+    )
+}
+
+/// Convert mongodb error to `LabeledError` which points at `span`.
+pub(crate) fn mongo_error_at(e: Error, span: Span) -> LabeledError {
+    let mut labels: Vec<String> = e.labels().iter().cloned().collect();
+    labels.sort();
+    let err = match *e.kind {
+        ErrorKind::Command(err) => {
+            server_error(err.message, err.code, Some(err.code_name), span)
+        }
+        ErrorKind::Write(WriteFailure::WriteError(err)) => {
+            server_error(err.message, err.code, err.code_name, span)
+        }
+        ErrorKind::Write(WriteFailure::WriteConcernError(err)) => write_concern_error(err, span),
+        ErrorKind::InsertMany(err) => insert_many_error(err, span),
+        ErrorKind::BulkWrite(err) => bulk_write_error(err, span),
+        ErrorKind::ServerSelection { message, .. } => LabeledError::new(message)
+            .with_label("no suitable server", span)
+            .with_help("Please check the url and network, or loosen `--read-preference`. Run `mongoc ping` to check the connection"),
+        ErrorKind::Authentication { message, .. } => LabeledError::new(message)
+            .with_label("authentication failed", span)
+            .with_help(AUTH_FAILED_HELP),
+        kind => LabeledError::new(kind.to_string()).with_label("mongodb error", span),
+    };
+    with_error_labels(err, &labels)
+}
+
+const AUTH_FAILED_HELP: &str =
+    "Please check the user name, password, `--auth-source` and `--auth-mechanism`";
+
+/// Help text of common server errors.
+fn help_of(code: i32) -> Option<&'static str> {
+    match code {
+        18 => Some(AUTH_FAILED_HELP),
+        13 => Some("The user doesn't have privileges to run this command"),
+        11000 => Some("A document with the same unique key already exists"),
+        _ => None,
+    }
+}
+
+fn server_error(message: String, code: i32, code_name: Option<String>, span: Span) -> LabeledError {
+    let code_name = code_name.filter(|name| !name.is_empty());
+    let label = match &code_name {
+        Some(name) => format!("{name} ({code})"),
+        None => format!("error code {code}"),
+    };
+    let mut err = LabeledError::new(message)
+        .with_label(label, span)
+        .with_code(format!(
+            "mongodb::{}",
+            code_name.unwrap_or(code.to_string())
+        ));
+    if let Some(help) = help_of(code) {
+        err = err.with_help(help);
+    }
+    err
+}
+
+fn write_concern_error(err: WriteConcernError, span: Span) -> LabeledError {
+    server_error(
+        format!("write concern error: {}", err.message),
+        err.code,
+        Some(err.code_name),
+        span,
+    )
+}
+
+/// Indexed write errors are attached as inner errors, so users can find failed documents.
+fn insert_many_error(err: InsertManyError, span: Span) -> LabeledError {
+    let write_errors = err.write_errors.unwrap_or_default();
+    let mut result = match (write_errors.first(), err.write_concern_error) {
+        (Some(first), _) => {
+            LabeledError::new(format!("{} documents failed to insert", write_errors.len()))
+                .with_label(format!("first failed at index {}", first.index), span)
+        }
+        (None, Some(wc_err)) => return write_concern_error(wc_err, span),
+        (None, None) => {
+            return LabeledError::new("insert many failed").with_label("mongodb error", span);
+        }
+    };
+    if let Some(help) = write_errors.first().and_then(|e| help_of(e.code)) {
+        result = result.with_help(help);
+    }
+    for e in write_errors {
+        result = result.with_inner(server_error(
+            format!("document at index {}: {}", e.index, e.message),
+            e.code,
+            e.code_name,
+            span,
+        ));
+    }
+    result
+}
+
+fn bulk_write_error(err: BulkWriteError, span: Span) -> LabeledError {
+    let mut write_errors: Vec<_> = err.write_errors.into_iter().collect();
+    write_errors.sort_by_key(|(index, _)| *index);
+    let mut result = match write_errors.first() {
+        Some((index, first)) => {
+            let mut result = LabeledError::new(format!(
+                "{} operations failed in bulk write",
+                write_errors.len()
+            ))
+            .with_label(format!("first failed at index {index}"), span);
+            if let Some(help) = help_of(first.code) {
+                result = result.with_help(help);
+            }
+            result
+        }
+        None => LabeledError::new("bulk write failed").with_label("mongodb error", span),
+    };
+    for (index, e) in write_errors {
+        result = result.with_inner(server_error(
+            format!("operation at index {index}: {}", e.message),
+            e.code,
+            e.code_name,
+            span,
+        ));
+    }
+    for e in err.write_concern_errors {
+        result = result.with_inner(write_concern_error(e, span));
+    }
+    result
+}
+
+fn with_error_labels(mut err: LabeledError, labels: &[String]) -> LabeledError {
+    if labels.is_empty() {
+        return err;
+    }
+    let labels = format!("error labels: {}", labels.join(", "));
+    err.help = Some(match err.help {
+        Some(help) => format!("{help}\n{labels}"),
+        None => labels,
+    });
+    err
+}
@@ -1,82 +1,414 @@
 mod cmds;
+mod completion_cache;
+mod connect_options;
+mod cursors;
+mod error;
+mod session;
 use cmds::*;
-use mongodb::sync::Database;
-use nu_plugin::{Plugin, PluginCommand};
-use nu_protocol::{LabeledError, Span};
+use completion_cache::CompletionCache;
+use connect_options::ConnectOptions;
+use cursors::CursorStore;
+use error::mongo_error_at;
+use mongodb::bson::UuidRepresentation;
+use mongodb::options::ClientOptions;
+use mongodb::sync::{Client, Database};
+use nu_plugin::{DynamicCompletionCall, EngineInterface, EvaluatedCall, Plugin, PluginCommand};
+use nu_protocol::{
+    DynamicSuggestion, Example, LabeledError, PipelineData, Signature, Span, Spanned, Value,
+    engine::ArgType,
+};
+use session::SessionEntry;
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Once, RwLock, mpsc};
+use std::time::Duration;
+
+/// How long `mongoc open` waits for the server by default.
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct HandleEntry {
+    client: Client,
+    db: Database,
+    /// connection string without password.
+    conn_str: String,
+    options: ConnectOptions,
+    client_options: ClientOptions,
+    /// database of the client used by completions, with its server selection timeout.
+    completion_db: Option<(Duration, Database)>,
+}
 
 struct Handle {
-    pub(crate) inner: HashMap<u8, (Database, String)>,
-    pub(crate) current: u8,
+    pub(crate) inner: HashMap<u32, HandleEntry>,
+    /// user-chosen names of handles.
+    pub(crate) aliases: HashMap<String, u32>,
+    pub(crate) current: u32,
+    /// next handle id to allocate, ids are never reused.
+    pub(crate) next_id: u32,
+    /// handles saved in session file, they are restored after plugin restarts.
+    pub(crate) persisted: HashMap<u32, SessionEntry>,
+    /// why persisted handles are not restored, reported when they're used.
+    pub(crate) not_restored: HashMap<u32, String>,
 }
 
 impl Handle {
     fn new() -> Self {
         Self {
             inner: HashMap::default(),
+            aliases: HashMap::default(),
             current: 0,
+            next_id: 0,
+            persisted: HashMap::default(),
+            not_restored: HashMap::default(),
+        }
+    }
+
+    /// Error of a handle which doesn't exist, tells why if it's a persisted handle not restored.
+    fn not_exist(&self, id: u32, span: Span) -> LabeledError {
+        match self.not_restored.get(&id) {
+            None => handle_not_exist(span),
+            Some(reason) => LabeledError::new("database handle is not restored")
+                .with_label(format!("persisted handle is not restored: {reason}"), span)
+                .with_help("Please fix it and restart the plugin by running `plugin stop mongo`, or run `mongoc forget` to stop restoring it"),
         }
     }
 }
 pub struct MongoPlugin {
     handlers: RwLock<Handle>,
+    restore: Once,
+    completions: CompletionCache,
+    cursors: CursorStore,
+}
+
+impl Default for MongoPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn handle_not_exist(span: Span) -> LabeledError {
+    LabeledError::new("database handle doesn't exist")
+        .with_label("not existed database handle", span)
+        .with_help("You can run `mongoc list` to list all available handles, or `mongoc open` to open a new handle")
+}
+
+fn name_exists(span: Span) -> LabeledError {
+    LabeledError::new("handle name already exists")
+        .with_label("duplicated handle name", span)
+        .with_help("You can run `mongoc remove` to remove the existing handle")
+}
+
+/// Create a client from connection string, returns it with its default database.
+fn open_database(
+    conn_str: &str,
+    password: Option<String>,
+    connect_options: &ConnectOptions,
+    span: Span,
+) -> Result<(Client, Database, ClientOptions), LabeledError> {
+    let mut options = ClientOptions::parse(conn_str)
+        .run()
+        .map_err(|err| mongo_error_at(err, span))?;
+    connect_options.apply(&mut options, password)?;
+    let client = Client::with_options(options.clone()).map_err(|err| mongo_error_at(err, span))?;
+    let db = client.default_database().ok_or_else(|| {
+        LabeledError::new("No default database in connection url")
+            .with_label("database is missing", span)
+    })?;
+    Ok((client, db, options))
+}
+
+/// Ping the server, gives up after `timeout`.
+fn verify_connection(db: &Database, timeout: Duration, span: Span) -> Result<(), LabeledError> {
+    let (sender, receiver) = mpsc::channel();
+    let db = db.clone();
+    std::thread::spawn(move || {
+        // receiver may be gone after timeout.
+        let _ = sender.send(ping_db(&db));
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(mongo_error_at(e, span)),
+        Err(_) => Err(LabeledError::new("can't connect to mongodb server")
+            .with_label("server doesn't respond", span)
+            .with_help(format!(
+                "Server doesn't respond in {timeout:?}, please check the url, or increase `--connect-timeout`"
+            )),
+        ),
+    }
 }
 
 impl MongoPlugin {
     pub fn new() -> Self {
         Self {
             handlers: RwLock::new(Handle::new()),
+            restore: Once::new(),
+            completions: CompletionCache::default(),
+            cursors: CursorStore::default(),
         }
     }
 
-    pub fn connect(&self, conn_str: &str) -> Result<u8, LabeledError> {
-        let conn = mongodb::sync::Client::with_uri_str(conn_str)
-            .map_err(|err| LabeledError::new(format!("{err}")))
-            .map(|c| c.default_database());
-        match conn {
-            Err(e) => return Err(e),
-            Ok(conn) => match conn {
-                None => return Err(LabeledError::new("No default database in connection url")),
-                Some(db) => {
-                    let mut write_guard = self.handlers.write().expect("write lock should success");
-                    let id = write_guard.inner.len() as u8;
-                    write_guard.inner.insert(id, (db, conn_str.to_string()));
-                    write_guard.current = id;
-                    Ok(id)
+    /// Restore persisted handles when the first command runs, passwords are read from `$env` of
+    /// the caller, because environment of the plugin process is not updated after it's spawned.
+    fn restore_handles(&self, engine: &EngineInterface) {
+        self.restore.call_once(|| self.restore_session(engine));
+    }
+
+    fn restore_session(&self, engine: &EngineInterface) {
+        let mut write_guard = self.handlers.write().expect("write lock should success");
+        for entry in session::load() {
+            let password = match &entry.password_env {
+                None => Ok(None),
+                Some(var) => engine
+                    .get_env_var(var)
+                    .ok()
+                    .flatten()
+                    .and_then(|v| v.coerce_into_string().ok())
+                    .map(Some)
+                    .ok_or_else(|| format!("environment variable `{var}` is not set")),
+            };
+            let opened = password.and_then(|password| {
+                open_database(&entry.url, password, &entry.options, Span::unknown())
+                    .map_err(|e| e.msg)
+            });
+            // keep broken handles in session file, so they can be forgotten.
+            match opened {
+                Err(reason) => {
+                    write_guard.not_restored.insert(entry.id, reason);
+                }
+                Ok((client, db, client_options)) => {
+                    if write_guard.inner.is_empty() {
+                        write_guard.current = entry.id;
+                    }
+                    write_guard.inner.insert(
+                        entry.id,
+                        HandleEntry {
+                            client,
+                            db,
+                            conn_str: entry.url.clone(),
+                            options: entry.options.clone(),
+                            client_options,
+                            completion_db: None,
+                        },
+                    );
+                    if let Some(name) = &entry.name {
+                        write_guard.aliases.insert(name.clone(), entry.id);
+                    }
                 }
+            }
+            write_guard.next_id = write_guard.next_id.max(entry.id.saturating_add(1));
+            write_guard.persisted.insert(entry.id, entry);
+        }
+    }
+
+    /// Open a new handle and select it, the server is pinged before the handle is added.
+    pub fn connect(
+        &self,
+        conn_str: &Spanned<String>,
+        password: Option<String>,
+        options: ConnectOptions,
+        name: Option<Spanned<String>>,
+    ) -> Result<u32, LabeledError> {
+        if let Some(name) = &name {
+            if name.item.parse::<i64>().is_ok() {
+                return Err(LabeledError::new("invalid handle name")
+                    .with_label("handle name can't be a number", name.span));
+            }
+            let read_guard = self.handlers.read().expect("read lock should success");
+            if read_guard.aliases.contains_key(&name.item) {
+                return Err(name_exists(name.span));
+            }
+        }
+        let (client, db, client_options) =
+            open_database(&conn_str.item, password, &options, conn_str.span)?;
+        verify_connection(
+            &db,
+            options.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+            conn_str.span,
+        )?;
+        let mut write_guard = self.handlers.write().expect("write lock should success");
+        // the name may be taken by another `mongoc open` while connecting.
+        if let Some(name) = &name
+            && write_guard.aliases.contains_key(&name.item)
+        {
+            return Err(name_exists(name.span));
+        }
+        let id = write_guard.next_id;
+        write_guard.next_id = id.checked_add(1).ok_or_else(|| {
+            LabeledError::new("database handle ids are exhausted")
+                .with_help("Please restart the plugin by running `plugin stop mongo`")
+        })?;
+        write_guard.inner.insert(
+            id,
+            HandleEntry {
+                client,
+                db,
+                conn_str: session::strip_password(&conn_str.item).unwrap_or(conn_str.item.clone()),
+                options,
+                client_options,
+                completion_db: None,
             },
+        );
+        if let Some(name) = name {
+            write_guard.aliases.insert(name.item, id);
         }
+        write_guard.current = id;
+        self.completions.invalidate(id, None);
+        Ok(id)
     }
 
-    pub fn list_handles(&self) -> Vec<(u8, String)> {
+    /// Save the handle into session file, so it's restored after plugin restarts.
+    ///
+    /// The password is not saved, it's read from `password_env` when the handle is restored.
+    pub fn persist_handle(
+        &self,
+        id: u32,
+        password_env: Option<String>,
+        span: Span,
+    ) -> Result<(), LabeledError> {
+        let mut write_guard = self.handlers.write().expect("write lock should success");
+        let entry = write_guard
+            .inner
+            .get(&id)
+            .ok_or_else(|| handle_not_exist(span))?;
+        let name = write_guard
+            .aliases
+            .iter()
+            .find(|(_, alias_id)| **alias_id == id)
+            .map(|(name, _)| name.to_string());
+        let session_entry = SessionEntry {
+            id,
+            name,
+            url: entry.conn_str.clone(),
+            password_env,
+            options: session::without_secrets(entry.options.clone()),
+        };
+        write_guard.persisted.insert(id, session_entry);
+        session::save(write_guard.persisted.values().cloned().collect())
+    }
+
+    /// Remove the handle from session file, the opened handle is kept.
+    pub fn forget_handle(&self, id: u32, span: Span) -> Result<(), LabeledError> {
+        let mut write_guard = self.handlers.write().expect("write lock should success");
+        if write_guard.persisted.remove(&id).is_none() {
+            return Err(LabeledError::new("database handle is not persisted")
+                .with_label("not persisted database handle", span));
+        }
+        write_guard.not_restored.remove(&id);
+        session::save(write_guard.persisted.values().cloned().collect())
+    }
+
+    /// List all handles, with their names and connection strings, passwords are not included.
+    pub fn list_handles(&self) -> Vec<(u32, Option<String>, String)> {
         let read_guard = self.handlers.read().expect("read lock should success");
         let mut result = vec![];
-        for (id, (_, conn_str)) in read_guard.inner.iter() {
-            result.push((*id, conn_str.to_string()));
+        for (id, entry) in read_guard.inner.iter() {
+            let name = read_guard
+                .aliases
+                .iter()
+                .find(|(_, alias_id)| *alias_id == id)
+                .map(|(name, _)| name.to_string());
+            result.push((*id, name, entry.conn_str.to_string()));
         }
         result
     }
 
-    pub fn get_handle(&self, id: u8, span: Span) -> Result<Database, LabeledError> {
+    /// Get handle id from user input, which can be a handle id or a handle name.
+    pub fn resolve_handle_id(&self, val: &Value) -> Result<u32, LabeledError> {
+        let span = val.span();
+        match val {
+            Value::Int { val, .. } => u32::try_from(*val).map_err(|_| {
+                LabeledError::new("database handle out of range")
+                    .with_label(format!("should be between 0 and {}", u32::MAX), span)
+            }),
+            Value::String { val, .. } => {
+                let read_guard = self.handlers.read().expect("read lock should success");
+                if let Some(id) = read_guard.aliases.get(val) {
+                    return Ok(*id);
+                }
+                // names of persisted handles which are not restored, so they can be forgotten, and
+                // other commands tell why they're not restored.
+                read_guard
+                    .persisted
+                    .values()
+                    .find(|entry| entry.name.as_ref() == Some(val))
+                    .map(|entry| entry.id)
+                    .ok_or_else(|| handle_not_exist(span))
+            }
+            other => Err(LabeledError::new("invalid database handle").with_label(
+                format!("expected handle id or name, got {}", other.get_type()),
+                span,
+            )),
+        }
+    }
+
+    /// Get handle id by alias name.
+    pub fn get_alias(&self, name: &str) -> Option<u32> {
         let read_guard = self.handlers.read().expect("read lock should success");
-        let result = read_guard.inner.get(&id).ok_or_else(|| {
-            let err = LabeledError::new("database handle doesn't exist")
-                .with_label("not existed database handle", span)
-                .with_help("You can run `mongoc list` to list all available handles, or `mongoc open` to open a new handle");
-            err
-        })?;
-        Ok(result.0.clone())
+        read_guard.aliases.get(name).copied()
+    }
+
+    pub fn get_handle(&self, id: u32, span: Span) -> Result<Database, LabeledError> {
+        let read_guard = self.handlers.read().expect("read lock should success");
+        let result = read_guard
+            .inner
+            .get(&id)
+            .ok_or_else(|| read_guard.not_exist(id, span))?;
+        Ok(result.db.clone())
     }
 
-    pub fn remove_handle(&self, id: u8, span: Span) -> Result<(), LabeledError> {
+    /// Get database of the handle for completions, which gives up after `server_timeout` if no
+    /// server is available, so an unreachable server doesn't keep refresh threads for long.
+    pub fn get_completion_handle(
+        &self,
+        id: u32,
+        server_timeout: Duration,
+    ) -> Result<Database, LabeledError> {
+        {
+            let read_guard = self.handlers.read().expect("read lock should success");
+            let entry = read_guard
+                .inner
+                .get(&id)
+                .ok_or_else(|| handle_not_exist(Span::unknown()))?;
+            if let Some((timeout, db)) = &entry.completion_db
+                && *timeout == server_timeout
+            {
+                return Ok(db.clone());
+            }
+        }
         let mut write_guard = self.handlers.write().expect("write lock should success");
-        write_guard.inner.remove(&id).ok_or_else(|| {
-            let err = LabeledError::new("database handle doesn't exist")
-                .with_label("not existed database handle", span)
-                .with_help("You can run `mongoc list` to list all available handles, or `mongoc open` to open a new handle");
-            err
-        })?;
+        let entry = write_guard
+            .inner
+            .get_mut(&id)
+            .ok_or_else(|| handle_not_exist(Span::unknown()))?;
+        let mut options = entry.client_options.clone();
+        options.server_selection_timeout = Some(server_timeout);
+        let client =
+            Client::with_options(options).map_err(|e| mongo_error_at(e, Span::unknown()))?;
+        let db = client.database(entry.db.name());
+        entry.completion_db = Some((server_timeout, db.clone()));
+        Ok(db)
+    }
+
+    /// Get the client of handle, it's required by operations across databases.
+    pub fn get_client(&self, id: u32, span: Span) -> Result<Client, LabeledError> {
+        let read_guard = self.handlers.read().expect("read lock should success");
+        let result = read_guard
+            .inner
+            .get(&id)
+            .ok_or_else(|| read_guard.not_exist(id, span))?;
+        Ok(result.client.clone())
+    }
+
+    pub fn remove_handle(&self, id: u32, span: Span) -> Result<(), LabeledError> {
+        let mut write_guard = self.handlers.write().expect("write lock should success");
+        write_guard
+            .inner
+            .remove(&id)
+            .ok_or_else(|| handle_not_exist(span))?;
+        write_guard.aliases.retain(|_, alias_id| *alias_id != id);
+        self.completions.invalidate(id, None);
+        self.cursors.remove_handle(id);
+        if write_guard.persisted.remove(&id).is_some() {
+            session::save(write_guard.persisted.values().cloned().collect())?;
+        }
         // if remove current handle, reset the id.
         if write_guard.current == id {
             let max_id = write_guard.inner.keys().max().unwrap_or(&0);
@@ -85,19 +417,31 @@ impl MongoPlugin {
         Ok(())
     }
 
-    pub fn select_handle(&self, id: u8, span: Span) -> Result<(), LabeledError> {
+    pub fn select_handle(&self, id: u32, span: Span) -> Result<(), LabeledError> {
         let mut write_guard = self.handlers.write().expect("read lock should success");
         if !write_guard.inner.contains_key(&id) {
-            let err = LabeledError::new("database handle doesn't exist")
-                .with_label("not existed database handle", span)
-                .with_help("You can run `mongoc list` to list all available handles, or `mongoc open` to open a new handle");
-            return Err(err);
+            return Err(write_guard.not_exist(id, span));
         }
         write_guard.current = id;
         Ok(())
     }
 
-    pub fn get_current(&self) -> Result<u8, LabeledError> {
+    /// Get UUID representation of the handle, given by `mongoc open --uuid-representation`.
+    pub fn get_uuid_representation(&self, id: u32) -> Option<UuidRepresentation> {
+        let read_guard = self.handlers.read().expect("read lock should success");
+        read_guard
+            .inner
+            .get(&id)
+            .and_then(|entry| entry.options.uuid_representation.as_deref())
+            .and_then(connect_options::uuid_representation)
+    }
+
+    /// Remove cached completions after collections are changed.
+    pub fn invalidate_completions(&self, id: u32, coll: Option<&str>) {
+        self.completions.invalidate(id, coll)
+    }
+
+    pub fn get_current(&self) -> Result<u32, LabeledError> {
         let read_guard = self.handlers.read().expect("read lock should success");
         if read_guard.inner.is_empty() {
             return Err(LabeledError::new("no database handles available")
@@ -113,21 +457,105 @@ impl Plugin for MongoPlugin {
     }
 
     fn commands(&self) -> Vec<Box<dyn PluginCommand<Plugin = Self>>> {
-        vec![
+        let commands: Vec<Box<dyn PluginCommand<Plugin = Self>>> = vec![
             Box::new(Open),
             Box::new(List),
             Box::new(Find),
             Box::new(FindOne),
+            Box::new(Next),
+            Box::new(ListCursors),
+            Box::new(KillCursor),
+            Box::new(Oid),
+            Box::new(UuidCmd),
+            Box::new(Decimal),
             Box::new(Drop),
+            Box::new(CreateColl),
+            Box::new(RenameColl),
+            Box::new(CollMod),
             Box::new(DeleteOne),
             Box::new(DeleteMany),
             Box::new(Remove),
+            Box::new(Forget),
             Box::new(MongoCmd),
             Box::new(Select),
             Box::new(ListCollectionNames),
             Box::new(ListIndexes),
             Box::new(Count),
             Box::new(Estimated),
-        ]
+            Box::new(Export),
+            Box::new(Import),
+            Box::new(BulkWrite),
+            Box::new(Schema),
+            Box::new(Ping),
+            Box::new(Info),
+            Box::new(GridFsCmd),
+            Box::new(GridFsList),
+            Box::new(GridFsGet),
+            Box::new(GridFsPut),
+            Box::new(GridFsDelete),
+            Box::new(GridFsRename),
+        ];
+        commands
+            .into_iter()
+            .map(|command| {
+                Box::new(RestoreHandles(command)) as Box<dyn PluginCommand<Plugin = Self>>
+            })
+            .collect()
+    }
+}
+
+/// Wraps a command to restore persisted handles before it runs or completes.
+struct RestoreHandles(Box<dyn PluginCommand<Plugin = MongoPlugin>>);
+
+impl PluginCommand for RestoreHandles {
+    type Plugin = MongoPlugin;
+
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn signature(&self) -> Signature {
+        self.0.signature()
+    }
+
+    fn description(&self) -> &str {
+        self.0.description()
+    }
+
+    fn extra_description(&self) -> &str {
+        self.0.extra_description()
+    }
+
+    fn search_terms(&self) -> Vec<&str> {
+        self.0.search_terms()
+    }
+
+    fn examples(&self) -> Vec<Example<'_>> {
+        self.0.examples()
+    }
+
+    fn run(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &EngineInterface,
+        call: &EvaluatedCall,
+        input: PipelineData,
+    ) -> Result<PipelineData, LabeledError> {
+        plugin.restore_handles(engine);
+        self.0.run(plugin, engine, call, input)
+    }
+
+    #[allow(deprecated)]
+    fn get_dynamic_completion(
+        &self,
+        plugin: &MongoPlugin,
+        engine: &EngineInterface,
+        call: DynamicCompletionCall,
+        arg_type: ArgType,
+        experimental: nu_protocol::engine::ExperimentalMarker,
+    ) -> Option<Vec<DynamicSuggestion>> {
+        plugin.restore_handles(engine);
+        self.0
+            .get_dynamic_completion(plugin, engine, call, arg_type, experimental)
     }
 }